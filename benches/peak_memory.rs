@@ -0,0 +1,93 @@
+//! 比较两种请求体生成方式的内存峰值
+//!
+//! 运行：`cargo bench --bench peak_memory`，可用 `RSOCR_BENCH_MB` 指定图片大小（默认50MB）。
+//!
+//! * 缓冲：`image_to_base64` 读取并编码整个文件，再序列化为 `OcrRequest` 的JSON
+//! * 流式：`OcrRequestBody` 边读取边编码，写入 `io::sink()`（代替HTTP连接）
+
+use rsocr::request_body::OcrRequestBody;
+use rsocr::{OcrOptions, OcrRequest, image_to_base64};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// 记录当前和峰值堆内存的分配器
+struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// 运行 `f`，返回期间相对于开始时的堆内存峰值
+fn measure(name: &str, f: impl FnOnce() -> rsocr::Result<u64>) -> rsocr::Result<usize> {
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    let started = Instant::now();
+    let written = f()?;
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+    println!(
+        "{:<6} 请求体 {:>6.1} MB  峰值 {:>7.2} MB  耗时 {:?}",
+        name,
+        written as f64 / MB as f64,
+        peak as f64 / MB as f64,
+        started.elapsed()
+    );
+    Ok(peak)
+}
+
+const MB: usize = 1024 * 1024;
+
+fn main() -> rsocr::Result<()> {
+    let size_mb: usize = std::env::var("RSOCR_BENCH_MB")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(50);
+
+    let mut image = tempfile::NamedTempFile::new()?;
+    let chunk: Vec<u8> = (0..MB).map(|i| (i * 7 % 251) as u8).collect();
+    for _ in 0..size_mb {
+        image.write_all(&chunk)?;
+    }
+    drop(chunk);
+    let path = image.path().to_str().unwrap().to_string();
+    let options = OcrOptions::default();
+
+    println!("图片大小 {} MB", size_mb);
+    let buffered = measure("缓冲", || {
+        let base64 = image_to_base64(&path)?;
+        let body = serde_json::to_vec(&OcrRequest {
+            base64: base64.clone(),
+            options: options.clone(),
+        })?;
+        io::sink().write_all(&body)?;
+        Ok(body.len() as u64)
+    })?;
+    let streamed = measure("流式", || {
+        let mut body = OcrRequestBody::from_file(&path, &options)?;
+        Ok(io::copy(&mut body, &mut io::sink())?)
+    })?;
+    println!(
+        "流式请求体的内存峰值为缓冲方式的 {:.2}%",
+        streamed as f64 / buffered as f64 * 100.0
+    );
+    Ok(())
+}
@@ -0,0 +1,393 @@
+//! 异步OCR客户端
+//!
+//! 需要启用 `async` 特性。[`AsyncOcrClient`] 基于 tokio 和异步 reqwest，
+//! 与 [`OcrClient`](crate::OcrClient) 使用相同的构建器、请求格式和错误类型，
+//! 适合在异步服务中并发识别多张图片。
+
+use crate::capabilities::{self, ServerCapabilities};
+use crate::client::{
+    DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT, OcrClientBuilder,
+    check_health_status, check_status, request_body,
+};
+use crate::models::{OcrOptions, OcrResponse, OcrResult, Result, RsOcrError};
+use crate::retry::{self, CircuitBreaker, Outcome, RetryPolicy};
+use crate::unix_socket::is_unix_url;
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::Client;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// 异步OCR客户端
+///
+/// 通过 [`OcrClientBuilder::build_async`] 创建。克隆开销很小，克隆出的实例共享同一个连接池，
+/// 可以在多个任务中并发使用。
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::AsyncOcrClient;
+///
+/// #[tokio::main]
+/// async fn main() -> rsocr::Result<()> {
+///     let client = AsyncOcrClient::new("http://127.0.0.1:1224/api/ocr")?;
+///
+///     let (a, b) = tokio::join!(client.recognize_file("a.png"), client.recognize_file("b.png"));
+///     println!("{}\n{}", a?.text(), b?.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncOcrClient {
+    http: Client,
+    url: String,
+    options: OcrOptions,
+    retry: RetryPolicy,
+    breaker: Option<CircuitBreaker>,
+}
+
+/// 兼容函数（如 [`call_ocr_api_async`]）共享的异步HTTP客户端
+fn shared_http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .timeout(DEFAULT_TIMEOUT)
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .unwrap_or_else(|_| Client::new())
+    })
+}
+
+impl AsyncOcrClient {
+    /// 创建构建器，调用 [`OcrClientBuilder::build_async`] 完成构建
+    ///
+    /// # 参数
+    /// * `url` - OCR API的URL
+    pub fn builder(url: impl Into<String>) -> OcrClientBuilder {
+        OcrClientBuilder::new(url)
+    }
+
+    /// 使用默认设置创建客户端
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        Self::builder(url).build_async()
+    }
+
+    pub(crate) fn from_parts(
+        http: Client,
+        url: String,
+        options: OcrOptions,
+        retry: RetryPolicy,
+        breaker: Option<CircuitBreaker>,
+    ) -> Self {
+        Self {
+            http,
+            url,
+            options,
+            retry,
+            breaker,
+        }
+    }
+
+    /// 使用共享的HTTP客户端创建客户端
+    ///
+    /// # 返回
+    /// * `Result<Self>` - API地址是 `unix://` 地址时返回 [`RsOcrError::Config`]
+    fn shared(url: &str) -> Result<Self> {
+        if is_unix_url(url) {
+            return Err(RsOcrError::Config(format!(
+                "异步客户端不支持Unix域套接字地址: {}",
+                url
+            )));
+        }
+        Ok(Self::from_parts(
+            shared_http_client().clone(),
+            url.to_string(),
+            OcrOptions::default(),
+            RetryPolicy::none(),
+            None,
+        ))
+    }
+
+    /// OCR API的URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 默认的OCR请求选项
+    pub fn options(&self) -> &OcrOptions {
+        &self.options
+    }
+
+    /// 使用默认选项识别base64编码的图片
+    pub async fn recognize(&self, base64_code: &str) -> Result<OcrResult> {
+        self.recognize_with_options(base64_code, &self.options)
+            .await
+    }
+
+    /// 使用指定选项识别base64编码的图片
+    ///
+    /// 与 [`OcrClient::recognize_with_options`](crate::OcrClient::recognize_with_options) 相同，
+    /// 请求总是以 [`DataFormat::Dict`](crate::DataFormat::Dict) 格式发送，并按相同的
+    /// 重试策略和熔断器处理失败。
+    pub async fn recognize_with_options(
+        &self,
+        base64_code: &str,
+        options: &OcrOptions,
+    ) -> Result<OcrResult> {
+        let body = request_body(base64_code, options);
+        let response =
+            retry::run_async(&self.retry, self.breaker.as_ref(), || {
+                let request = self
+                    .http
+                    .post(&self.url)
+                    .header("Content-Type", "application/json")
+                    .json(&body);
+                async move {
+                    Outcome::classify(&self.retry, request.send().await, |r| r.status().as_u16())
+                }
+            })
+            .await?;
+        check_status(response.status())?;
+
+        let ocr_response: OcrResponse = response.json().await.map_err(RsOcrError::Http)?;
+        ocr_response.into_result()
+    }
+
+    /// 使用默认选项识别图片文件
+    pub async fn recognize_file(&self, image_path: &str) -> Result<OcrResult> {
+        self.recognize_file_with_options(image_path, &self.options)
+            .await
+    }
+
+    /// 使用指定选项识别图片文件，文件通过 `tokio::fs` 异步读取
+    pub async fn recognize_file_with_options(
+        &self,
+        image_path: &str,
+        options: &OcrOptions,
+    ) -> Result<OcrResult> {
+        let base64_code = image_to_base64_async(image_path).await?;
+        self.recognize_with_options(&base64_code, options).await
+    }
+
+    /// 检查OCR服务是否可访问，只有服务器错误（5xx）视为不可用
+    pub async fn health(&self) -> Result<()> {
+        let response = self
+            .http
+            .get(&self.url)
+            .send()
+            .await
+            .map_err(RsOcrError::Http)?;
+        check_health_status(response.status())
+    }
+
+    /// 查询服务器支持的OCR参数，与同步客户端共享按API地址的缓存
+    pub async fn capabilities(&self) -> Result<ServerCapabilities> {
+        if let Some(cached) = capabilities::cached(&self.url) {
+            return Ok(cached);
+        }
+
+        let response = self
+            .http
+            .get(capabilities::options_url(&self.url))
+            .send()
+            .await
+            .map_err(RsOcrError::Http)?;
+        capabilities::check_status(response.status())?;
+
+        let json: Value = response.json().await.map_err(RsOcrError::Http)?;
+        let result = ServerCapabilities::from_json(&json);
+        capabilities::store(&self.url, &result);
+        Ok(result)
+    }
+
+    /// 发送请求前校验OCR请求选项，见 [`capabilities::validate_options`]
+    pub async fn validate_options(&self, options: &OcrOptions) -> Result<()> {
+        if !capabilities::needs_validation(options) {
+            return Ok(());
+        }
+        capabilities::validate_against(self.capabilities().await, options)
+    }
+}
+
+/// 异步读取图片文件并转换为base64编码
+///
+/// # 参数
+/// * `image_path` - 图片文件路径
+///
+/// # 返回
+/// * `Result<String>` - 成功时返回base64编码字符串，失败时返回错误
+pub async fn image_to_base64_async(image_path: &str) -> Result<String> {
+    let buffer = tokio::fs::read(image_path).await.map_err(RsOcrError::Io)?;
+    Ok(general_purpose::STANDARD.encode(&buffer))
+}
+
+/// [`call_ocr_api`](crate::call_ocr_api) 的异步版本
+///
+/// # 参数
+/// * `base64_code` - 图片的base64编码
+/// * `url` - OCR API的URL
+///
+/// # 返回
+/// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+pub async fn call_ocr_api_async(base64_code: &str, url: &str) -> Result<OcrResult> {
+    call_ocr_api_with_options_async(base64_code, url, &OcrOptions::default()).await
+}
+
+/// [`call_ocr_api_with_options`](crate::call_ocr_api_with_options) 的异步版本
+///
+/// # 参数
+/// * `base64_code` - 图片的base64编码
+/// * `url` - OCR API的URL
+/// * `options` - OCR请求选项
+///
+/// # 返回
+/// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+pub async fn call_ocr_api_with_options_async(
+    base64_code: &str,
+    url: &str,
+    options: &OcrOptions,
+) -> Result<OcrResult> {
+    AsyncOcrClient::shared(url)?
+        .recognize_with_options(base64_code, options)
+        .await
+}
+
+/// [`recognize_file`](crate::recognize_file) 的异步版本
+///
+/// # 参数
+/// * `image_path` - 图片文件路径
+/// * `url` - OCR API的URL
+/// * `options` - OCR请求选项
+///
+/// # 返回
+/// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+pub async fn recognize_file_async(
+    image_path: &str,
+    url: &str,
+    options: &OcrOptions,
+) -> Result<OcrResult> {
+    AsyncOcrClient::shared(url)?
+        .recognize_file_with_options(image_path, options)
+        .await
+}
+
+/// [`check_health`](crate::check_health) 的异步版本
+pub async fn check_health_async(url: &str) -> Result<()> {
+    AsyncOcrClient::shared(url)?.health().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+    use std::time::Duration;
+
+    fn echo_server() -> MockServer {
+        MockServer::start(|request| {
+            let text = request.json()["base64"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            MockResponse::umi_text(&text, 1.0)
+        })
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_recognize() -> Result<()> {
+        let server = echo_server();
+        let client = AsyncOcrClient::builder(format!("{}/api/ocr", server.url()))
+            .header("X-Trace", "async")
+            .build_async()?;
+
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move { client.recognize(&format!("img{}", i)).await })
+            })
+            .collect();
+
+        for (i, task) in tasks.into_iter().enumerate() {
+            let result = task.await.expect("任务异常退出")?;
+            assert_eq!(result.text(), format!("img{}", i));
+        }
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 8);
+        assert!(
+            requests
+                .iter()
+                .all(|r| r.header("x-trace") == Some("async"))
+        );
+        assert!(
+            requests
+                .iter()
+                .all(|r| r.json()["options"]["data.format"] == "dict")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recognize_file_and_errors() -> Result<()> {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::text(200, "ok"),
+            _ => MockResponse::json(serde_json::json!({"code": 902, "data": "图片解码失败"})),
+        });
+        let url = format!("{}/api/ocr", server.url());
+
+        check_health_async(&url).await?;
+
+        let temp_file = tempfile::NamedTempFile::new().map_err(RsOcrError::Io)?;
+        std::fs::write(temp_file.path(), b"fake").map_err(RsOcrError::Io)?;
+        let path = temp_file.path().to_str().unwrap();
+
+        let err = recognize_file_async(path, &url, &OcrOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrServer { code: 902, .. }));
+        assert_eq!(
+            server.requests()[1].json()["base64"],
+            general_purpose::STANDARD.encode(b"fake")
+        );
+
+        let err = call_ocr_api_async("x", "http://127.0.0.1:1/api/ocr")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::Http(_)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() -> Result<()> {
+        let server = MockServer::start(|_| {
+            std::thread::sleep(Duration::from_millis(1500));
+            MockResponse::json(serde_json::json!({"code": 101}))
+        });
+        let client = AsyncOcrClient::builder(format!("{}/api/ocr", server.url()))
+            .timeout(Duration::from_millis(200))
+            .build_async()?;
+
+        let err = client.recognize("aGVsbG8=").await.unwrap_err();
+        assert!(matches!(err, RsOcrError::Http(ref e) if e.is_timeout()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_connect_error() -> Result<()> {
+        let client = AsyncOcrClient::builder("http://127.0.0.1:1/api/ocr")
+            .retry(RetryPolicy {
+                initial_backoff: Duration::from_millis(10),
+                ..Default::default()
+            })
+            .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+            .build_async()?;
+
+        // 每个请求重试3次，但只计一次失败
+        for _ in 0..2 {
+            let err = client.recognize("x").await.unwrap_err();
+            assert!(matches!(err, RsOcrError::Http(ref e) if e.is_connect()));
+        }
+        let err = client.recognize("x").await.unwrap_err();
+        assert!(matches!(err, RsOcrError::CircuitOpen(_)));
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+//! 认证信息与密钥引用
+//!
+//! 配置文件中不直接保存密钥，而是保存对密钥的引用（[`SecretRef`]）：
+//!
+//! * `env:NAME` - 从环境变量 `NAME` 读取
+//! * `file:PATH` - 从文件读取（去掉末尾的换行），Unix 下文件权限必须为 `0600` 或更严格
+//! * 其他字符串 - 明文，可以使用但会输出警告
+//!
+//! 密钥只在构建客户端时解析为 [`Secret`]，`Secret` 和明文引用的 `Debug`/`Display`
+//! 输出都会隐藏内容，写入请求头时也会标记为敏感值。
+
+use crate::models::{Result, RsOcrError};
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::header::{AUTHORIZATION, HeaderName, HeaderValue};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 隐藏后的显示内容
+const REDACTED: &str = "***";
+
+/// API Key 认证默认使用的请求头
+pub const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+/// 已解析的密钥，`Debug` 和 `Display` 不会输出内容
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// 包装密钥
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// 取出密钥内容
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// 对密钥的引用
+///
+/// 在配置文件中写作字符串：`env:NAME`、`file:PATH` 或明文。
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::auth::SecretRef;
+///
+/// let token: SecretRef = "env:OCR_TOKEN".parse().unwrap();
+/// assert_eq!(token, SecretRef::Env("OCR_TOKEN".to_string()));
+/// assert_eq!(format!("{:?}", SecretRef::Plain("hunter2".to_string())), "Plain(***)");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    /// 环境变量
+    Env(String),
+    /// 权限为 `0600` 的文件
+    File(PathBuf),
+    /// 明文
+    Plain(String),
+}
+
+impl SecretRef {
+    /// 解析引用，取得密钥
+    ///
+    /// # 返回
+    /// * `Result<Secret>` - 环境变量未设置、文件无法读取或权限过宽时返回 [`RsOcrError::Config`]
+    pub fn resolve(&self) -> Result<Secret> {
+        match self {
+            Self::Env(name) => std::env::var(name)
+                .map(Secret)
+                .map_err(|_| RsOcrError::Config(format!("环境变量 {} 未设置", name))),
+            Self::File(path) => read_secret_file(path),
+            Self::Plain(value) => {
+                log::warn!("配置中包含明文密钥，建议改用 env: 或 file: 引用");
+                Ok(Secret(value.clone()))
+            }
+        }
+    }
+}
+
+/// 读取密钥文件，检查文件权限
+fn read_secret_file(path: &Path) -> Result<Secret> {
+    let error = |reason: String| {
+        RsOcrError::Config(format!("无法读取密钥文件 {}: {}", path.display(), reason))
+    };
+    let metadata = fs::metadata(path).map_err(|e| error(e.to_string()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(error(format!("文件权限 {:o} 过宽，请执行 chmod 600", mode)));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    let content = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    Ok(Secret(content.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+impl std::str::FromStr for SecretRef {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(if let Some(name) = s.strip_prefix("env:") {
+            Self::Env(name.to_string())
+        } else if let Some(path) = s.strip_prefix("file:") {
+            Self::File(PathBuf::from(path))
+        } else {
+            Self::Plain(s.to_string())
+        })
+    }
+}
+
+impl fmt::Display for SecretRef {
+    /// 输出配置文件中的写法，明文被隐藏
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env:{}", name),
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Plain(_) => f.write_str(REDACTED),
+        }
+    }
+}
+
+impl fmt::Debug for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "Env({:?})", name),
+            Self::File(path) => write!(f, "File({:?})", path),
+            Self::Plain(_) => write!(f, "Plain({})", REDACTED),
+        }
+    }
+}
+
+impl Serialize for SecretRef {
+    /// 按配置文件中的写法保存，明文原样保存以免丢失
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Plain(value) => serializer.serialize_str(value),
+            other => serializer.serialize_str(&other.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|never| match never {}))
+    }
+}
+
+/// 请求认证方式
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::OcrClient;
+/// use rsocr::auth::{Auth, SecretRef};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::builder("https://ocr.internal/api/ocr")
+///         .auth(Auth::Bearer(SecretRef::Env("OCR_TOKEN".to_string())))
+///         .build()?;
+///     println!("{}", client.recognize_file("image.png")?.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// `Authorization: Bearer <token>`
+    Bearer(SecretRef),
+    /// `Authorization: Basic <base64(username:password)>`
+    Basic {
+        username: String,
+        password: SecretRef,
+    },
+    /// 在指定请求头（默认 [`DEFAULT_API_KEY_HEADER`]）中发送密钥
+    ApiKey { header: String, key: SecretRef },
+}
+
+impl Auth {
+    /// 解析密钥并生成请求头，请求头的值标记为敏感
+    ///
+    /// # 返回
+    /// * `Result<(HeaderName, HeaderValue)>` - 密钥无法解析或不是有效的请求头时返回 [`RsOcrError::Config`]
+    pub fn header(&self) -> Result<(HeaderName, HeaderValue)> {
+        let (name, value) = match self {
+            Self::Bearer(token) => (
+                AUTHORIZATION,
+                format!("Bearer {}", token.resolve()?.expose()),
+            ),
+            Self::Basic { username, password } => (
+                AUTHORIZATION,
+                format!(
+                    "Basic {}",
+                    general_purpose::STANDARD.encode(format!(
+                        "{}:{}",
+                        username,
+                        password.resolve()?.expose()
+                    ))
+                ),
+            ),
+            Self::ApiKey { header, key } => (
+                HeaderName::from_bytes(header.as_bytes()).map_err(|e| {
+                    RsOcrError::Config(format!("无效的请求头名称 '{}': {}", header, e))
+                })?,
+                key.resolve()?.expose().to_string(),
+            ),
+        };
+
+        // 错误信息中不能包含密钥
+        let mut value = HeaderValue::from_str(&value)
+            .map_err(|_| RsOcrError::Config(format!("认证信息不是有效的 {} 请求头", name)))?;
+        value.set_sensitive(true);
+        Ok((name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_secret_refs() -> Result<()> {
+        let plain: SecretRef = "hunter2".parse().unwrap();
+        assert_eq!(plain.resolve()?.expose(), "hunter2");
+        assert!(!format!("{:?} {}", plain, plain).contains("hunter2"));
+        assert!(!format!("{:?} {}", plain.resolve()?, plain.resolve()?).contains("hunter2"));
+
+        let missing = SecretRef::Env("RSOCR_TEST_SECRET_MISSING".to_string());
+        assert!(matches!(missing.resolve(), Err(RsOcrError::Config(_))));
+        let path = std::env::var("PATH").unwrap_or_default();
+        assert_eq!(
+            "env:PATH".parse::<SecretRef>().unwrap().resolve()?.expose(),
+            path
+        );
+
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "from-file")?;
+        let secret = SecretRef::File(file.path().to_path_buf());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(file.path(), fs::Permissions::from_mode(0o644))?;
+            let err = secret.resolve().unwrap_err();
+            assert!(err.to_string().contains("644"));
+            fs::set_permissions(file.path(), fs::Permissions::from_mode(0o600))?;
+        }
+        assert_eq!(secret.resolve()?.expose(), "from-file");
+        assert_eq!(
+            format!("file:{}", file.path().display())
+                .parse::<SecretRef>()
+                .unwrap(),
+            secret
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_auth_header() -> Result<()> {
+        let (name, value) = Auth::Basic {
+            username: "user".to_string(),
+            password: SecretRef::Plain("pass".to_string()),
+        }
+        .header()?;
+        assert_eq!(name, AUTHORIZATION);
+        assert_eq!(value, "Basic dXNlcjpwYXNz");
+        assert!(value.is_sensitive());
+        assert!(!format!("{:?}", value).contains("dXNlcjpwYXNz"));
+
+        let (name, value) = Auth::ApiKey {
+            header: DEFAULT_API_KEY_HEADER.to_string(),
+            key: SecretRef::Plain("k".to_string()),
+        }
+        .header()?;
+        assert_eq!(name, "x-api-key");
+        assert_eq!(value, "k");
+
+        let err = Auth::Bearer(SecretRef::Plain("bad\nvalue".to_string()))
+            .header()
+            .unwrap_err();
+        assert!(!err.to_string().contains("bad"));
+        Ok(())
+    }
+}
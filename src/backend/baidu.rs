@@ -0,0 +1,416 @@
+//! 百度智能云文字识别后端
+//!
+//! 调用 `general_basic`（通用文字识别）或 `accurate`（高精度含位置）接口。
+//! 认证使用 API Key 和 Secret Key 换取 `access_token`（`/oauth/2.0/token`），
+//! 令牌在有效期内缓存，服务器报告令牌失效（110/111）时重新获取并重试一次。
+//! 业务错误（`error_code`）返回 [`RsOcrError::OcrServer`]。
+
+use super::{BoxFormat, OcrBackend};
+use crate::auth::Secret;
+use crate::client::{OcrClient, check_status};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use crate::retry::{self, Outcome};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认的接口根地址
+pub const DEFAULT_BAIDU_URL: &str = "https://aip.baidubce.com";
+
+/// 表示 `access_token` 无效或过期的错误码
+const TOKEN_ERRORS: [i64; 2] = [110, 111];
+
+/// 提前刷新令牌的时间
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+/// 识别接口
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BaiduModel {
+    /// 通用文字识别（标准版），不返回位置
+    #[default]
+    GeneralBasic,
+    /// 通用文字识别（高精度含位置版）
+    Accurate,
+}
+
+impl BaiduModel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::GeneralBasic => "general_basic",
+            Self::Accurate => "accurate",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Credentials {
+    /// 预先获取的令牌
+    Token(Secret),
+    /// 用 API Key 和 Secret Key 换取令牌
+    Keys {
+        api_key: Secret,
+        secret_key: Secret,
+        cached: Arc<Mutex<Option<(Secret, Instant)>>>,
+    },
+}
+
+/// 百度智能云文字识别后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::{BaiduBackend, BaiduModel};
+/// use rsocr::{OcrBackend, OcrClient, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::new("https://aip.baidubce.com")?;
+///     let backend = BaiduBackend::new(client, "api key", "secret key").model(BaiduModel::Accurate);
+///     let result = backend.recognize_path("scan.png", &OcrOptions::default())?;
+///     println!("{}", result.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BaiduBackend {
+    client: OcrClient,
+    endpoint: String,
+    credentials: Credentials,
+    language_type: Option<String>,
+}
+
+impl BaiduBackend {
+    /// 使用 API Key 和 Secret Key 创建后端
+    ///
+    /// # 参数
+    /// * `client` - 客户端，其URL为接口根地址（如 [`DEFAULT_BAIDU_URL`]）
+    /// * `api_key` - 应用的 API Key
+    /// * `secret_key` - 应用的 Secret Key
+    pub fn new(
+        client: OcrClient,
+        api_key: impl Into<Secret>,
+        secret_key: impl Into<Secret>,
+    ) -> Self {
+        Self::with_credentials(
+            client,
+            Credentials::Keys {
+                api_key: api_key.into(),
+                secret_key: secret_key.into(),
+                cached: Arc::default(),
+            },
+        )
+    }
+
+    /// 使用预先获取的 `access_token` 创建后端，令牌失效时不会自动刷新
+    pub fn with_token(client: OcrClient, access_token: impl Into<Secret>) -> Self {
+        Self::with_credentials(client, Credentials::Token(access_token.into()))
+    }
+
+    fn with_credentials(client: OcrClient, credentials: Credentials) -> Self {
+        Self {
+            endpoint: Self::endpoint_for(&client, BaiduModel::default()),
+            client,
+            credentials,
+            language_type: None,
+        }
+    }
+
+    fn endpoint_for(client: &OcrClient, model: BaiduModel) -> String {
+        format!(
+            "{}/rest/2.0/ocr/v1/{}",
+            client.url().trim_end_matches('/'),
+            model.as_str()
+        )
+    }
+
+    /// 设置识别接口
+    pub fn model(mut self, model: BaiduModel) -> Self {
+        self.endpoint = Self::endpoint_for(&self.client, model);
+        self
+    }
+
+    /// 设置识别语言（`language_type`，如 `CHN_ENG`、`JAP`）
+    pub fn language_type(mut self, language_type: impl Into<String>) -> Self {
+        self.language_type = Some(language_type.into());
+        self
+    }
+
+    /// 取得 `access_token`，需要时向服务器申请
+    ///
+    /// # 参数
+    /// * `refresh` - 忽略缓存的令牌
+    fn access_token(&self, refresh: bool) -> Result<Secret> {
+        let (api_key, secret_key, cached) = match &self.credentials {
+            Credentials::Token(token) => return Ok(token.clone()),
+            Credentials::Keys {
+                api_key,
+                secret_key,
+                cached,
+            } => (api_key, secret_key, cached),
+        };
+
+        let mut cached = cached.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((token, expires)) = cached.as_ref()
+            && !refresh
+            && Instant::now() < *expires
+        {
+            return Ok(token.clone());
+        }
+
+        let url = format!(
+            "{}/oauth/2.0/token",
+            self.client.url().trim_end_matches('/')
+        );
+        let response = self
+            .client
+            .http()
+            .post(url)
+            .query(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", api_key.expose()),
+                ("client_secret", secret_key.expose()),
+            ])
+            .send()
+            .map_err(|e| RsOcrError::Http(e.without_url()))?;
+        let status = response.status();
+        let json: Value = response
+            .json()
+            .map_err(|e| RsOcrError::Http(e.without_url()))?;
+
+        let Some(token) = json.get("access_token").and_then(Value::as_str) else {
+            let reason = json
+                .get("error_description")
+                .or_else(|| json.get("error"))
+                .and_then(Value::as_str);
+            if reason.is_none() {
+                check_status(status)?;
+            }
+            return Err(RsOcrError::OcrApi(format!(
+                "获取百度access_token失败: {}",
+                reason.unwrap_or("响应中没有access_token")
+            )));
+        };
+        let expires_in = json
+            .get("expires_in")
+            .and_then(Value::as_u64)
+            .map(Duration::from_secs)
+            .unwrap_or(TOKEN_MARGIN);
+        let token = Secret::new(token);
+        *cached = Some((
+            token.clone(),
+            Instant::now() + expires_in.saturating_sub(TOKEN_MARGIN),
+        ));
+        Ok(token)
+    }
+
+    fn recognize_with_token(&self, token: &Secret, image: &str) -> Result<Value> {
+        let mut form = vec![("image", image), ("probability", "true")];
+        if let Some(language_type) = &self.language_type {
+            form.push(("language_type", language_type));
+        }
+
+        let response = retry::run(
+            self.client.retry_policy(),
+            self.client.circuit_breaker(),
+            || {
+                let response = self
+                    .client
+                    .http()
+                    .post(&self.endpoint)
+                    .query(&[("access_token", token.expose())])
+                    .form(&form)
+                    .send()
+                    .map_err(reqwest::Error::without_url);
+                Outcome::classify(self.client.retry_policy(), response, |r| {
+                    r.status().as_u16()
+                })
+            },
+        )?;
+        check_status(response.status())?;
+        response
+            .json()
+            .map_err(|e| RsOcrError::Http(e.without_url()))
+    }
+}
+
+/// 解析识别结果
+fn parse_response(response: &Value) -> Result<OcrResult> {
+    if let Some(code) = response.get("error_code").and_then(Value::as_i64) {
+        return Err(RsOcrError::OcrServer {
+            code,
+            message: response
+                .get("error_msg")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+
+    let words = response
+        .get("words_result")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    Ok(OcrResult {
+        blocks: words
+            .iter()
+            .map(|word| TextBlock {
+                text: word
+                    .get("words")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                score: word
+                    .pointer("/probability/average")
+                    .and_then(Value::as_f64)
+                    .unwrap_or(1.0),
+                bbox: word
+                    .get("location")
+                    .and_then(|l| BoxFormat::Xywh.parse(l))
+                    .unwrap_or_default(),
+                end: "\n".to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+impl OcrBackend for BaiduBackend {
+    fn name(&self) -> &'static str {
+        "baidu"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// 识别语言由 [`language_type`](Self::language_type) 控制，`options` 会被忽略
+    fn recognize_bytes(&self, image: &[u8], _options: &OcrOptions) -> Result<OcrResult> {
+        let image = general_purpose::STANDARD.encode(image);
+        let response = self.recognize_with_token(&self.access_token(false)?, &image)?;
+
+        let token_expired = response
+            .get("error_code")
+            .and_then(Value::as_i64)
+            .is_some_and(|code| TOKEN_ERRORS.contains(&code));
+        if token_expired && matches!(self.credentials, Credentials::Keys { .. }) {
+            let response = self.recognize_with_token(&self.access_token(true)?, &image)?;
+            return parse_response(&response);
+        }
+        parse_response(&response)
+    }
+
+    /// 检查能否取得 `access_token`
+    fn health(&self) -> Result<()> {
+        self.access_token(false).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockRequest, MockResponse, MockServer};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn form(request: &MockRequest) -> Vec<(String, String)> {
+        reqwest::Url::parse(&format!(
+            "http://form/?{}",
+            String::from_utf8_lossy(&request.body)
+        ))
+        .unwrap()
+        .query_pairs()
+        .into_owned()
+        .collect()
+    }
+
+    #[test]
+    fn test_token_flow() -> Result<()> {
+        let tokens = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| {
+            if request.path.starts_with("/oauth/2.0/token") {
+                let n = tokens.fetch_add(1, Ordering::SeqCst);
+                return MockResponse::json(
+                    json!({"access_token": format!("token-{}", n), "expires_in": 2592000}),
+                );
+            }
+            if request.path.ends_with("token-0") {
+                return MockResponse::json(
+                    json!({"error_code": 111, "error_msg": "Access token expired"}),
+                );
+            }
+            MockResponse::json(json!({
+                "log_id": 1,
+                "words_result_num": 2,
+                "words_result": [
+                    {"words": "百度识别", "location": {"left": 10, "top": 20, "width": 100, "height": 30}, "probability": {"average": 0.98}},
+                    {"words": "second"}
+                ]
+            }))
+        });
+        let backend = BaiduBackend::new(OcrClient::new(server.url())?, "ak", "sk")
+            .model(BaiduModel::Accurate)
+            .language_type("CHN_ENG");
+        assert_eq!(
+            backend.endpoint(),
+            format!("{}/rest/2.0/ocr/v1/accurate", server.url())
+        );
+
+        let result = backend.recognize_bytes(b"img", &OcrOptions::default())?;
+        assert_eq!(result.text(), "百度识别\nsecond");
+        assert_eq!(result.blocks[0].score, 0.98);
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 20], [110, 20], [110, 50], [10, 50]]
+        );
+
+        // 第二次识别使用缓存的令牌
+        backend.recognize_bytes(b"img", &OcrOptions::default())?;
+        let requests = server.requests();
+        let paths: Vec<&str> = requests.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/oauth/2.0/token?grant_type=client_credentials&client_id=ak&client_secret=sk",
+                "/rest/2.0/ocr/v1/accurate?access_token=token-0",
+                "/oauth/2.0/token?grant_type=client_credentials&client_id=ak&client_secret=sk",
+                "/rest/2.0/ocr/v1/accurate?access_token=token-1",
+                "/rest/2.0/ocr/v1/accurate?access_token=token-1",
+            ]
+        );
+        let form = form(&requests[1]);
+        assert!(form.contains(&("image".to_string(), "aW1n".to_string())));
+        assert!(form.contains(&("language_type".to_string(), "CHN_ENG".to_string())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<()> {
+        let server = MockServer::start(|request| {
+            if request.path.starts_with("/oauth") {
+                MockResponse::status_json(
+                    401,
+                    json!({"error": "invalid_client", "error_description": "unknown client id"}),
+                )
+            } else {
+                MockResponse::json(json!({"error_code": 216201, "error_msg": "image format error"}))
+            }
+        });
+
+        let backend = BaiduBackend::new(OcrClient::new(server.url())?, "bad", "bad");
+        let err = backend.health().unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrApi(ref m) if m.contains("unknown client id")));
+
+        let backend = BaiduBackend::with_token(OcrClient::new(server.url())?, "static");
+        let err = backend
+            .recognize_bytes(b"img", &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RsOcrError::OcrServer { code: 216201, ref message } if message == "image format error"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,470 @@
+//! Google Cloud Vision 后端
+//!
+//! 调用 `images:annotate` 接口的 `TEXT_DETECTION` 或 `DOCUMENT_TEXT_DETECTION` 功能。
+//! 认证使用API Key（`X-Goog-Api-Key` 请求头）或OAuth访问令牌（`Authorization: Bearer`）。
+//! 访问令牌通常在1小时后过期，后端不会刷新，长期运行时应使用API Key。
+//! 结果优先取自 `fullTextAnnotation`：按符号的 `detectedBreak` 拼接成行，
+//! 行的坐标为其中各个单词外接矩形的并集；没有 `fullTextAnnotation` 时每个单词作为一个文本块。
+
+use super::{BoxFormat, OcrBackend, rect_box};
+use crate::auth::Secret;
+use crate::client::{OcrClient, check_status};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use crate::retry::{self, Outcome};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+/// 默认的接口根地址
+pub const DEFAULT_GOOGLE_URL: &str = "https://vision.googleapis.com";
+
+/// 识别功能
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GoogleFeature {
+    /// `TEXT_DETECTION`，适合照片中的零散文字
+    #[default]
+    Text,
+    /// `DOCUMENT_TEXT_DETECTION`，适合密集排版的文档
+    Document,
+}
+
+impl GoogleFeature {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "TEXT_DETECTION",
+            Self::Document => "DOCUMENT_TEXT_DETECTION",
+        }
+    }
+}
+
+/// Google Cloud Vision 后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::{GoogleFeature, GoogleVisionBackend};
+/// use rsocr::{OcrBackend, OcrClient, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::new("https://vision.googleapis.com")?;
+///     let backend = GoogleVisionBackend::new(client)
+///         .api_key("AIza...")
+///         .feature(GoogleFeature::Document);
+///     let result = backend.recognize_path("scan.png", &OcrOptions::default())?;
+///     println!("{}", result.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GoogleVisionBackend {
+    client: OcrClient,
+    endpoint: String,
+    api_key: Option<Secret>,
+    access_token: Option<Secret>,
+    feature: GoogleFeature,
+    language_hints: Vec<String>,
+}
+
+impl GoogleVisionBackend {
+    /// 创建后端
+    ///
+    /// # 参数
+    /// * `client` - 客户端，其URL为接口根地址（如 [`DEFAULT_GOOGLE_URL`]）
+    pub fn new(client: OcrClient) -> Self {
+        let endpoint = format!("{}/v1/images:annotate", client.url().trim_end_matches('/'));
+        Self {
+            client,
+            endpoint,
+            api_key: None,
+            access_token: None,
+            feature: GoogleFeature::default(),
+            language_hints: Vec::new(),
+        }
+    }
+
+    /// 使用API Key认证
+    pub fn api_key(mut self, key: impl Into<Secret>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// 使用OAuth访问令牌认证（如 `gcloud auth print-access-token` 的输出）
+    ///
+    /// 令牌通常在1小时后过期，过期后请求返回401，需要换上新的令牌重新创建后端。
+    pub fn access_token(mut self, token: impl Into<Secret>) -> Self {
+        self.access_token = Some(token.into());
+        self
+    }
+
+    /// 设置识别功能
+    pub fn feature(mut self, feature: GoogleFeature) -> Self {
+        self.feature = feature;
+        self
+    }
+
+    /// 设置语言提示（BCP-47，如 `zh`、`en`）
+    pub fn language_hints(mut self, hints: Vec<String>) -> Self {
+        self.language_hints = hints;
+        self
+    }
+
+    fn request_body(&self, image: &[u8]) -> Value {
+        let mut request = json!({
+            "image": {"content": general_purpose::STANDARD.encode(image)},
+            "features": [{"type": self.feature.as_str()}]
+        });
+        if !self.language_hints.is_empty() {
+            request["imageContext"] = json!({"languageHints": self.language_hints});
+        }
+        json!({"requests": [request]})
+    }
+
+    /// 带上认证信息的 `images:annotate` 请求
+    fn post(&self, body: &Value) -> reqwest::blocking::RequestBuilder {
+        let mut request = self.client.http().post(&self.endpoint).json(body);
+        if let Some(key) = &self.api_key {
+            request = request.header("X-Goog-Api-Key", key.expose());
+        }
+        if let Some(token) = &self.access_token {
+            request = request.bearer_auth(token.expose());
+        }
+        request
+    }
+}
+
+/// 把错误对象转换为 [`RsOcrError::OcrServer`]
+fn server_error(error: &Value) -> RsOcrError {
+    RsOcrError::OcrServer {
+        code: error.get("code").and_then(Value::as_i64).unwrap_or(-1),
+        message: error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// 解析 `images:annotate` 的响应
+fn parse_response(response: &Value) -> Result<OcrResult> {
+    if let Some(error) = response.get("error") {
+        return Err(server_error(error));
+    }
+    let annotation = response.pointer("/responses/0").unwrap_or(&Value::Null);
+    if let Some(error) = annotation.get("error") {
+        return Err(server_error(error));
+    }
+
+    let blocks = match annotation.get("fullTextAnnotation") {
+        Some(full) => document_lines(full),
+        None => annotation
+            .get("textAnnotations")
+            .and_then(Value::as_array)
+            .map(|words| {
+                // 第一项是全文，其后才是各个单词
+                words
+                    .iter()
+                    .skip(1)
+                    .map(|word| TextBlock {
+                        text: word
+                            .get("description")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        score: 1.0,
+                        bbox: vertices(word.get("boundingPoly"))
+                            .and_then(|v| BoxFormat::Points.parse(&v))
+                            .unwrap_or_default(),
+                        end: " ".to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    Ok(OcrResult {
+        blocks,
+        ..Default::default()
+    })
+}
+
+/// 取出多边形的顶点，Google 会省略值为0的坐标
+fn vertices(poly: Option<&Value>) -> Option<Value> {
+    let points = poly?.get("vertices")?.as_array()?;
+    Some(Value::Array(
+        points
+            .iter()
+            .map(|p| {
+                json!([
+                    p.get("x").and_then(Value::as_i64).unwrap_or(0),
+                    p.get("y").and_then(Value::as_i64).unwrap_or(0)
+                ])
+            })
+            .collect(),
+    ))
+}
+
+/// 行内累积的文字、外接矩形和置信度
+#[derive(Default)]
+struct Line {
+    text: String,
+    bounds: Option<[i32; 4]>,
+    scores: Vec<f64>,
+}
+
+impl Line {
+    fn add_box(&mut self, quad: [[i32; 2]; 4]) {
+        for [x, y] in quad {
+            let [l, t, r, b] = self.bounds.get_or_insert([x, y, x, y]);
+            *l = (*l).min(x);
+            *t = (*t).min(y);
+            *r = (*r).max(x);
+            *b = (*b).max(y);
+        }
+    }
+
+    fn flush(&mut self, blocks: &mut Vec<TextBlock>) {
+        let line = std::mem::take(self);
+        let text = line.text.trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        let score = if line.scores.is_empty() {
+            1.0
+        } else {
+            line.scores.iter().sum::<f64>() / line.scores.len() as f64
+        };
+        let [l, t, r, b] = line.bounds.unwrap_or_default();
+        blocks.push(TextBlock {
+            text,
+            score,
+            bbox: rect_box(l, t, r, b),
+            end: "\n".to_string(),
+        });
+    }
+}
+
+/// 取出数组成员，不存在时为空
+fn items<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// 按 `detectedBreak` 把 `fullTextAnnotation` 中的单词拼接成行
+fn document_lines(full: &Value) -> Vec<TextBlock> {
+    let mut blocks = Vec::new();
+    let mut line = Line::default();
+
+    for page in items(full, "pages") {
+        for block in items(page, "blocks") {
+            for paragraph in items(block, "paragraphs") {
+                for word in items(paragraph, "words") {
+                    if let Some(quad) =
+                        vertices(word.get("boundingBox")).and_then(|v| BoxFormat::Points.parse(&v))
+                    {
+                        line.add_box(quad);
+                    }
+                    if let Some(confidence) = word.get("confidence").and_then(Value::as_f64) {
+                        line.scores.push(confidence);
+                    }
+                    for symbol in items(word, "symbols") {
+                        line.text
+                            .push_str(symbol.get("text").and_then(Value::as_str).unwrap_or(""));
+                        match symbol
+                            .pointer("/property/detectedBreak/type")
+                            .and_then(Value::as_str)
+                        {
+                            Some("SPACE" | "SURE_SPACE") => line.text.push(' '),
+                            Some("HYPHEN") => {
+                                line.text.push('-');
+                                line.flush(&mut blocks);
+                            }
+                            Some("EOL_SURE_SPACE" | "LINE_BREAK") => line.flush(&mut blocks),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            line.flush(&mut blocks);
+        }
+    }
+
+    blocks
+}
+
+impl OcrBackend for GoogleVisionBackend {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// 识别参数由 [`feature`](Self::feature) 和 [`language_hints`](Self::language_hints) 控制，
+    /// `options` 会被忽略
+    fn recognize_bytes(&self, image: &[u8], _options: &OcrOptions) -> Result<OcrResult> {
+        let body = self.request_body(image);
+        let response = retry::run(
+            self.client.retry_policy(),
+            self.client.circuit_breaker(),
+            || {
+                let response = self.post(&body).send().map_err(reqwest::Error::without_url);
+                Outcome::classify(self.client.retry_policy(), response, |r| {
+                    r.status().as_u16()
+                })
+            },
+        )?;
+        let status = response.status();
+
+        // 错误响应的JSON中包含更详细的信息
+        match response.json::<Value>() {
+            Ok(json) => {
+                let result = parse_response(&json)?;
+                check_status(status)?;
+                Ok(result)
+            }
+            Err(e) => {
+                check_status(status)?;
+                Err(RsOcrError::Http(e.without_url()))
+            }
+        }
+    }
+
+    /// 发送空的批量请求检查认证信息，不会产生识别费用
+    ///
+    /// 认证失败时返回401/403；空请求本身可能被判为参数错误（400），这说明认证已经通过。
+    fn health(&self) -> Result<()> {
+        let response = self
+            .post(&json!({"requests": []}))
+            .send()
+            .map_err(|e| RsOcrError::Http(e.without_url()))?;
+        let status = response.status();
+        match status.as_u16() {
+            400 => Ok(()),
+            401 | 403 => match response.json::<Value>() {
+                Ok(json) if json.get("error").is_some() => Err(server_error(&json["error"])),
+                _ => check_status(status),
+            },
+            _ => check_status(status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    fn word(text: &str, brk: Option<&str>, x: i64, confidence: f64) -> Value {
+        let mut symbol = json!({"text": text});
+        if let Some(brk) = brk {
+            symbol["property"] = json!({"detectedBreak": {"type": brk}});
+        }
+        json!({
+            "confidence": confidence,
+            "boundingBox": {"vertices": [{"x": x, "y": 10}, {"x": x + 20, "y": 10}, {"x": x + 20, "y": 30}, {"y": 30}]},
+            "symbols": [symbol]
+        })
+    }
+
+    #[test]
+    fn test_document_text() -> Result<()> {
+        let server = MockServer::start(|_| {
+            MockResponse::json(json!({"responses": [{
+                "textAnnotations": [{"description": "ignored"}],
+                "fullTextAnnotation": {"pages": [{"blocks": [{"paragraphs": [{"words": [
+                    word("Hello", Some("SPACE"), 5, 0.9),
+                    word("World", Some("EOL_SURE_SPACE"), 30, 0.7),
+                    word("你好", None, 5, 1.0)
+                ]}]}]}]}
+            }]}))
+        });
+        let backend = GoogleVisionBackend::new(OcrClient::new(server.url())?)
+            .api_key("secret key")
+            .feature(GoogleFeature::Document)
+            .language_hints(vec!["zh".to_string()]);
+
+        let result = backend.recognize_bytes(b"img", &OcrOptions::default())?;
+        assert_eq!(result.text(), "Hello World\n你好");
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[0, 10], [50, 10], [50, 30], [0, 30]]
+        );
+        assert!((result.blocks[0].score - 0.8).abs() < 1e-9);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v1/images:annotate");
+        assert_eq!(request.header("x-goog-api-key"), Some("secret key"));
+        let body = request.json();
+        assert_eq!(body["requests"][0]["image"]["content"], "aW1n");
+        assert_eq!(
+            body["requests"][0]["features"][0]["type"],
+            "DOCUMENT_TEXT_DETECTION"
+        );
+        assert_eq!(
+            body["requests"][0]["imageContext"]["languageHints"],
+            json!(["zh"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_words_and_errors() -> Result<()> {
+        let server = MockServer::start(|request| {
+            if request.header("authorization") == Some("Bearer expired") {
+                MockResponse::status_json(
+                    401,
+                    json!({"error": {"code": 401, "message": "Request had invalid authentication credentials."}}),
+                )
+            } else {
+                MockResponse::json(json!({"responses": [{"textAnnotations": [
+                    {"description": "A B"},
+                    {"description": "A", "boundingPoly": {"vertices": [{"x": 1, "y": 1}, {"x": 9, "y": 1}, {"x": 9, "y": 9}, {"x": 1, "y": 9}]}},
+                    {"description": "B"}
+                ]}]}))
+            }
+        });
+
+        let backend = GoogleVisionBackend::new(OcrClient::new(server.url())?).access_token("ok");
+        let result = backend.recognize_bytes(b"img", &OcrOptions::default())?;
+        assert_eq!(result.blocks.len(), 2);
+        assert_eq!(result.blocks[0].bbox, [[1, 1], [9, 1], [9, 9], [1, 9]]);
+        assert_eq!(
+            server.requests()[0].header("authorization"),
+            Some("Bearer ok")
+        );
+
+        let backend =
+            GoogleVisionBackend::new(OcrClient::new(server.url())?).access_token("expired");
+        let err = backend
+            .recognize_bytes(b"img", &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrServer { code: 401, .. }));
+
+        // 健康检查会校验令牌
+        let backend = GoogleVisionBackend::new(OcrClient::new(server.url())?).access_token("ok");
+        backend.health()?;
+        let backend =
+            GoogleVisionBackend::new(OcrClient::new(server.url())?).access_token("expired");
+        assert!(matches!(
+            backend.health(),
+            Err(RsOcrError::OcrServer { code: 401, .. })
+        ));
+        let request = server.requests().pop().unwrap();
+        assert_eq!(request.path, "/v1/images:annotate");
+        assert_eq!(request.json(), json!({"requests": []}));
+
+        let err = parse_response(
+            &json!({"responses": [{"error": {"code": 3, "message": "Bad image data."}}]}),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, RsOcrError::OcrServer { code: 3, ref message } if message == "Bad image data.")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,518 @@
+//! 模板化的通用HTTP后端
+//!
+//! 通过配置文件描述请求和响应的格式，即可接入任意OCR HTTP服务：
+//!
+//! * 请求体由JSON模板生成，字符串中的 `{{base64}}`、`{{filename}}`、`{{language}}` 等占位符
+//!   会被替换；整个字符串只有一个占位符时按原始类型替换（如 `"{{cls}}"` 替换为布尔值）
+//! * 也可以改用 multipart 表单上传图片
+//! * 响应通过 [`JsonPath`] 选择器映射为文字、坐标和置信度
+
+use super::json_path::JsonPath;
+use super::{BoxFormat, OcrBackend};
+use crate::client::{OcrClient, check_status};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use crate::retry::{self, Outcome};
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::blocking::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// 未配置模板时使用的请求体，与Umi-OCR的请求格式相同
+pub const DEFAULT_TEMPLATE: &str = r#"{"base64": "{{base64}}", "options": "{{options}}"}"#;
+
+/// 请求体的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyKind {
+    /// 由JSON模板生成的请求体
+    #[default]
+    Json,
+    /// multipart 表单，图片作为文件字段上传
+    Multipart,
+}
+
+/// 响应映射（配置文件中的 `[http.response]` 表）
+///
+/// 选择器的写法见 [`JsonPath`]。`text`、`score` 和 `box` 相对于 `blocks` 选出的每个元素。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseMapping {
+    /// 选出文本块列表，如 `$.data[*]`；未设置时把整个响应当作一个文本块
+    pub blocks: Option<String>,
+    /// 文本块中的文字，默认为 `$.text`
+    pub text: Option<String>,
+    /// 文本块的置信度，未设置时为 1.0；大于1的值按百分比处理
+    pub score: Option<String>,
+    /// 文本块的坐标
+    #[serde(rename = "box")]
+    pub bbox: Option<String>,
+    /// 坐标的格式
+    pub box_format: Option<BoxFormat>,
+    /// 错误信息，选出非空值时视为识别失败
+    pub error: Option<String>,
+}
+
+/// 解析后的响应映射
+#[derive(Debug, Clone)]
+struct CompiledMapping {
+    blocks: Option<JsonPath>,
+    text: JsonPath,
+    score: Option<JsonPath>,
+    bbox: Option<JsonPath>,
+    box_format: BoxFormat,
+    error: Option<JsonPath>,
+}
+
+impl CompiledMapping {
+    fn new(mapping: &ResponseMapping) -> Result<Self> {
+        let parse = |path: &Option<String>| path.as_deref().map(JsonPath::parse).transpose();
+        Ok(Self {
+            blocks: parse(&mapping.blocks)?,
+            text: JsonPath::parse(mapping.text.as_deref().unwrap_or("$.text"))?,
+            score: parse(&mapping.score)?,
+            bbox: parse(&mapping.bbox)?,
+            box_format: mapping.box_format.unwrap_or_default(),
+            error: parse(&mapping.error)?,
+        })
+    }
+
+    fn map(&self, response: &Value) -> Result<OcrResult> {
+        if let Some(error) = self.error.as_ref().and_then(|p| p.first(response))
+            && !is_blank(error)
+        {
+            return Err(RsOcrError::OcrApi(value_to_string(error)));
+        }
+
+        let items = match &self.blocks {
+            Some(path) => path.select(response),
+            None => vec![response],
+        };
+
+        let blocks = items
+            .into_iter()
+            .filter_map(|item| {
+                let text = self
+                    .text
+                    .select(item)
+                    .into_iter()
+                    .map(value_to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if text.is_empty() {
+                    return None;
+                }
+                let score = self
+                    .score
+                    .as_ref()
+                    .and_then(|p| p.first(item))
+                    .and_then(Value::as_f64)
+                    .map_or(1.0, |s| if s > 1.0 { s / 100.0 } else { s });
+                let bbox = self
+                    .bbox
+                    .as_ref()
+                    .and_then(|p| p.first(item))
+                    .and_then(|v| self.box_format.parse(v))
+                    .unwrap_or_default();
+                Some(TextBlock {
+                    text,
+                    score,
+                    bbox,
+                    end: "\n".to_string(),
+                })
+            })
+            .collect();
+
+        Ok(OcrResult {
+            blocks,
+            ..Default::default()
+        })
+    }
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        Value::Bool(true) | Value::Number(_) => false,
+    }
+}
+
+/// 把JSON值转换为文字：字符串去掉引号，数组按行拼接，null 为空
+pub(crate) fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+/// 模板化的通用HTTP后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::{HttpBackend, ResponseMapping};
+/// use rsocr::{OcrBackend, OcrClient, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::new("http://ocr.internal/v1/recognize")?;
+///     let backend = HttpBackend::new(
+///         client,
+///         Some(r#"{"image": "{{base64}}", "lang": "{{language}}"}"#),
+///         &ResponseMapping {
+///             blocks: Some("$.lines[*]".to_string()),
+///             score: Some("$.confidence".to_string()),
+///             ..Default::default()
+///         },
+///     )?;
+///     println!("{}", backend.recognize_path("image.png", &OcrOptions::default())?.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpBackend {
+    client: OcrClient,
+    template: Value,
+    body: BodyKind,
+    file_field: String,
+    form: BTreeMap<String, String>,
+    mapping: CompiledMapping,
+}
+
+impl HttpBackend {
+    /// 创建后端
+    ///
+    /// # 参数
+    /// * `client` - 提供URL、超时、请求头和重试策略的客户端
+    /// * `template` - JSON请求体模板，`None` 时使用 [`DEFAULT_TEMPLATE`]
+    /// * `mapping` - 响应映射
+    ///
+    /// # 返回
+    /// * `Result<HttpBackend>` - 模板或选择器无效时返回 [`RsOcrError::Config`]
+    pub fn new(
+        client: OcrClient,
+        template: Option<&str>,
+        mapping: &ResponseMapping,
+    ) -> Result<Self> {
+        let template = serde_json::from_str(template.unwrap_or(DEFAULT_TEMPLATE))
+            .map_err(|e| RsOcrError::Config(format!("无效的请求模板: {}", e)))?;
+        Ok(Self {
+            client,
+            template,
+            body: BodyKind::Json,
+            file_field: "file".to_string(),
+            form: BTreeMap::new(),
+            mapping: CompiledMapping::new(mapping)?,
+        })
+    }
+
+    /// 改用 multipart 表单上传图片
+    ///
+    /// # 参数
+    /// * `file_field` - 图片文件字段的名称
+    /// * `form` - 其他文本字段，值中可以使用与JSON模板相同的占位符
+    pub fn multipart(
+        mut self,
+        file_field: impl Into<String>,
+        form: BTreeMap<String, String>,
+    ) -> Self {
+        self.body = BodyKind::Multipart;
+        self.file_field = file_field.into();
+        self.form = form;
+        self
+    }
+
+    fn recognize(&self, image: &[u8], filename: &str, options: &OcrOptions) -> Result<OcrResult> {
+        let vars = template_vars(image, filename, options);
+        let body = match self.body {
+            BodyKind::Json => Some(render(&self.template, &vars)),
+            BodyKind::Multipart => None,
+        };
+
+        let response = retry::run(
+            self.client.retry_policy(),
+            self.client.circuit_breaker(),
+            || {
+                let request = self.client.http().post(self.client.url());
+                let request = match &body {
+                    Some(body) => request.json(body),
+                    None => request.multipart(self.form(image, filename, &vars)),
+                };
+                Outcome::classify(self.client.retry_policy(), request.send(), |r| {
+                    r.status().as_u16()
+                })
+            },
+        )?;
+        check_status(response.status())?;
+
+        let json: Value = response.json().map_err(RsOcrError::Http)?;
+        self.mapping.map(&json)
+    }
+
+    fn form(&self, image: &[u8], filename: &str, vars: &Map<String, Value>) -> Form {
+        let file = Part::bytes(image.to_vec()).file_name(filename.to_string());
+        let file = file
+            .mime_str(mime_type(filename))
+            .unwrap_or_else(|_| Part::bytes(image.to_vec()).file_name(filename.to_string()));
+
+        self.form.iter().fold(
+            Form::new().part(self.file_field.clone(), file),
+            |form, (name, value)| form.text(name.clone(), interpolate(value, vars)),
+        )
+    }
+}
+
+/// 按扩展名猜测图片的MIME类型
+pub(crate) fn mime_type(filename: &str) -> &'static str {
+    let extension = filename
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "bmp" => "image/bmp",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 模板中可用的占位符
+fn template_vars(image: &[u8], filename: &str, options: &OcrOptions) -> Map<String, Value> {
+    let mut vars = Map::new();
+    vars.insert(
+        "base64".into(),
+        general_purpose::STANDARD.encode(image).into(),
+    );
+    vars.insert("filename".into(), filename.into());
+    vars.insert("language".into(), options.language.clone().into());
+    vars.insert("cls".into(), options.cls.into());
+    vars.insert("limit_side_len".into(), options.limit_side_len.into());
+    vars.insert("parser".into(), options.parser.map(|p| p.as_str()).into());
+    vars.insert(
+        "ignore_area".into(),
+        serde_json::to_value(&options.ignore_area).unwrap_or_default(),
+    );
+    vars.insert(
+        "options".into(),
+        serde_json::to_value(options).unwrap_or_default(),
+    );
+    vars
+}
+
+/// 替换模板中的占位符
+fn render(template: &Value, vars: &Map<String, Value>) -> Value {
+    match template {
+        Value::String(s) => {
+            let name = s
+                .strip_prefix("{{")
+                .and_then(|s| s.strip_suffix("}}"))
+                .map(str::trim)
+                .filter(|name| !name.contains("{{"));
+            match name {
+                Some(name) => vars.get(name).cloned().unwrap_or(Value::Null),
+                None => Value::String(interpolate(s, vars)),
+            }
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| render(v, vars)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render(v, vars)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// 在字符串中替换占位符，未知的占位符原样保留
+fn interpolate(text: &str, vars: &Map<String, Value>) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) if vars.contains_key(after[..end].trim()) => {
+                output.push_str(&value_to_string(&vars[after[..end].trim()]));
+                rest = &after[end + 2..];
+            }
+            _ => {
+                output.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+impl OcrBackend for HttpBackend {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn endpoint(&self) -> &str {
+        self.client.url()
+    }
+
+    fn recognize_bytes(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult> {
+        self.recognize(image, "image", options)
+    }
+
+    fn recognize_path(&self, image_path: &str, options: &OcrOptions) -> Result<OcrResult> {
+        let image = std::fs::read(image_path).map_err(RsOcrError::Io)?;
+        let filename = std::path::Path::new(image_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "image".to_string());
+        self.recognize(&image, &filename, options)
+    }
+
+    fn health(&self) -> Result<()> {
+        self.client.health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn lines_response() -> MockResponse {
+        MockResponse::json(json!({
+            "error": null,
+            "lines": [
+                {"words": "Hello", "confidence": 98, "rect": [1, 2, 11, 12]},
+                {"words": "", "confidence": 50, "rect": [0, 0, 0, 0]},
+                {"words": "world", "confidence": 0.75, "rect": [1, 20, 11, 30]}
+            ]
+        }))
+    }
+
+    fn mapping() -> ResponseMapping {
+        ResponseMapping {
+            blocks: Some("$.lines[*]".to_string()),
+            text: Some("$.words".to_string()),
+            score: Some("$.confidence".to_string()),
+            bbox: Some("$.rect".to_string()),
+            box_format: Some(BoxFormat::Xyxy),
+            error: Some("$.error".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let options = OcrOptions {
+            cls: Some(true),
+            ..Default::default()
+        };
+        let vars = template_vars(b"img", "a.png", &options);
+        let template = json!({
+            "image": "{{base64}}",
+            "meta": {"name": "file: {{ filename }}", "cls": "{{cls}}", "lang": "{{language}}"},
+            "keep": "{{unknown}} {{",
+            "n": 1
+        });
+
+        assert_eq!(
+            render(&template, &vars),
+            json!({
+                "image": "aW1n",
+                "meta": {"name": "file: a.png", "cls": true, "lang": null},
+                "keep": "{{unknown}} {{",
+                "n": 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_template() -> Result<()> {
+        let server = MockServer::start(|_| lines_response());
+        let client = OcrClient::new(format!("{}/v1/ocr", server.url()))?;
+        let backend = HttpBackend::new(
+            client,
+            Some(
+                r#"{"img": "{{base64}}", "name": "{{filename}}", "opts": {"lang": "{{language}}"}}"#,
+            ),
+            &mapping(),
+        )?;
+
+        let options = OcrOptions {
+            language: Some("en".to_string()),
+            ..Default::default()
+        };
+        let result = backend.recognize_bytes(b"img", &options)?;
+        assert_eq!(result.text(), "Hello\nworld");
+        assert_eq!(result.blocks[0].score, 0.98);
+        assert_eq!(result.blocks[0].bbox, [[1, 2], [11, 2], [11, 12], [1, 12]]);
+        assert_eq!(result.blocks[1].score, 0.75);
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.json(),
+            json!({"img": "aW1n", "name": "image", "opts": {"lang": "en"}})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multipart_and_errors() -> Result<()> {
+        let server = MockServer::start(|request| {
+            if request.path == "/fail" {
+                MockResponse::json(json!({"error": "unsupported image"}))
+            } else {
+                lines_response()
+            }
+        });
+
+        let temp_dir = tempfile::TempDir::new().map_err(RsOcrError::Io)?;
+        let image_path = temp_dir.path().join("scan.png");
+        std::fs::write(&image_path, b"PNGDATA").map_err(RsOcrError::Io)?;
+
+        let client = OcrClient::new(format!("{}/upload", server.url()))?;
+        let form = BTreeMap::from([("lang".to_string(), "{{language}}-x".to_string())]);
+        let backend = HttpBackend::new(client, None, &mapping())?.multipart("image", form);
+        let options = OcrOptions {
+            language: Some("zh".to_string()),
+            ..Default::default()
+        };
+        let result = backend.recognize_path(image_path.to_str().unwrap(), &options)?;
+        assert_eq!(result.blocks.len(), 2);
+
+        let request = &server.requests()[0];
+        assert!(
+            request
+                .header("content-type")
+                .unwrap()
+                .starts_with("multipart/form-data")
+        );
+        let body = String::from_utf8_lossy(&request.body);
+        assert!(body.contains("name=\"image\"; filename=\"scan.png\""));
+        assert!(body.contains("Content-Type: image/png"));
+        assert!(body.contains("PNGDATA"));
+        assert!(body.contains("name=\"lang\"\r\n\r\nzh-x"));
+
+        let client = OcrClient::new(format!("{}/fail", server.url()))?;
+        let backend = HttpBackend::new(client, None, &mapping())?;
+        let err = backend.recognize_bytes(b"x", &options).unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrApi(ref m) if m == "unsupported image"));
+
+        let client = OcrClient::new(server.url())?;
+        assert!(matches!(
+            HttpBackend::new(client, Some("{not json"), &mapping()),
+            Err(RsOcrError::Config(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//! JSONPath风格的选择器
+//!
+//! 支持JSONPath的一个常用子集，用于从各种OCR服务的响应中取出文字、坐标和置信度：
+//!
+//! * `$` - 根节点（可省略）
+//! * `.key` 或 `['key']` - 对象成员
+//! * `[0]`、`[-1]` - 数组元素，负数从末尾计数
+//! * `[*]` 或 `.*` - 数组的全部元素或对象的全部成员
+
+use crate::models::{Result, RsOcrError};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// 解析后的选择器
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// 解析选择器
+    ///
+    /// # 参数
+    /// * `path` - 如 `$.data.lines[*].text`
+    ///
+    /// # 返回
+    /// * `Result<JsonPath>` - 语法错误时返回 [`RsOcrError::Config`]
+    pub fn parse(path: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| RsOcrError::Config(format!("无效的选择器 '{}': {}", path, reason));
+        let mut segments = Vec::new();
+        let mut rest = path.trim();
+        rest = rest.strip_prefix('$').unwrap_or(rest);
+
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(|| invalid("缺少 ]"))?;
+                let inner = after[..end].trim();
+                rest = &after[end + 1..];
+
+                segments.push(if inner == "*" {
+                    Segment::Wildcard
+                } else if let Some(key) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    Segment::Key(key.to_string())
+                } else {
+                    Segment::Index(inner.parse().map_err(|_| invalid("下标不是整数"))?)
+                });
+            } else {
+                let after = rest.strip_prefix('.').unwrap_or(rest);
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let key = &after[..end];
+                if key.is_empty() {
+                    return Err(invalid("成员名称为空"));
+                }
+                segments.push(if key == "*" {
+                    Segment::Wildcard
+                } else {
+                    Segment::Key(key.to_string())
+                });
+                rest = &after[end..];
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// 选出全部匹配的值
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for segment in &self.segments {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&Value> {
+                    match (segment, value) {
+                        (Segment::Key(key), Value::Object(map)) => {
+                            map.get(key).into_iter().collect()
+                        }
+                        (Segment::Index(index), Value::Array(items)) => {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+                            usize::try_from(index)
+                                .ok()
+                                .and_then(|i| items.get(i))
+                                .into_iter()
+                                .collect()
+                        }
+                        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+
+    /// 选出第一个匹配的值
+    pub fn first<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.select(value).into_iter().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select() -> Result<()> {
+        let value = json!({
+            "data": {"lines": [{"text": "a", "score": 0.5}, {"text": "b"}]},
+            "odd key": [1, 2, 3]
+        });
+
+        let texts = JsonPath::parse("$.data.lines[*].text")?.select(&value);
+        assert_eq!(texts, vec![&json!("a"), &json!("b")]);
+        assert_eq!(
+            JsonPath::parse("data.lines[-1].text")?.first(&value),
+            Some(&json!("b"))
+        );
+        assert_eq!(
+            JsonPath::parse("$['odd key'][1]")?.first(&value),
+            Some(&json!(2))
+        );
+        assert_eq!(JsonPath::parse("$")?.first(&value), Some(&value));
+        assert!(
+            JsonPath::parse("$.data.missing[0]")?
+                .select(&value)
+                .is_empty()
+        );
+
+        assert!(matches!(
+            JsonPath::parse("$.a[1"),
+            Err(RsOcrError::Config(_))
+        ));
+        assert!(JsonPath::parse("$.a[x]").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+//! 视觉大模型后端
+//!
+//! 通过 OpenAI 兼容的 `/v1/chat/completions` 接口（Ollama、llama.cpp、vLLM 等）识别文字：
+//! 图片以 `data:` URL 的形式放在用户消息中，模型的回复按行拆分为文本块。
+//! 启用 JSON 模式时要求模型输出 `{"lines": [{"text": .., "box": [x0, y0, x1, y1]}]}`，
+//! 解析失败时退回到按行拆分。
+
+use super::http::value_to_string;
+use super::json_path::JsonPath;
+use super::{BoxFormat, OcrBackend};
+use crate::client::{OcrClient, check_status};
+use crate::image_format;
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use crate::retry::{self, Outcome};
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::{Value, json};
+
+/// 默认的接口地址（Ollama）
+pub const DEFAULT_LLM_URL: &str = "http://127.0.0.1:11434/v1/chat/completions";
+
+/// 默认的转写提示词
+pub const DEFAULT_LLM_PROMPT: &str =
+    "请逐行转写图片中的全部文字，保持原有的换行，只输出文字本身，不要添加任何解释。";
+
+/// JSON 模式下追加的提示词
+const JSON_PROMPT: &str = "以JSON格式输出，格式为 {\"lines\": [{\"text\": \"一行文字\", \"box\": [x0, y0, x1, y1]}]}，\
+坐标为该行文字在图片中的像素位置。只输出JSON。";
+
+/// 默认的最大生成长度
+pub const DEFAULT_LLM_MAX_TOKENS: u32 = 2048;
+
+/// 视觉大模型后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::LlmBackend;
+/// use rsocr::{OcrBackend, OcrClient, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::new("http://127.0.0.1:11434/v1/chat/completions")?;
+///     let backend = LlmBackend::new(client, "qwen2.5vl:7b").json_boxes(true);
+///     let result = backend.recognize_path("photo.jpg", &OcrOptions::default())?;
+///     println!("{}", result.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LlmBackend {
+    client: OcrClient,
+    model: String,
+    prompt: String,
+    max_tokens: u32,
+    json_boxes: bool,
+}
+
+impl LlmBackend {
+    /// 创建后端
+    ///
+    /// # 参数
+    /// * `client` - 提供接口地址、超时、请求头和重试策略的客户端
+    /// * `model` - 模型名称
+    pub fn new(client: OcrClient, model: impl Into<String>) -> Self {
+        Self {
+            client,
+            model: model.into(),
+            prompt: DEFAULT_LLM_PROMPT.to_string(),
+            max_tokens: DEFAULT_LLM_MAX_TOKENS,
+            json_boxes: false,
+        }
+    }
+
+    /// 设置提示词
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// 设置最大生成长度
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// 是否要求模型以JSON格式输出每行文字及其坐标
+    pub fn json_boxes(mut self, enabled: bool) -> Self {
+        self.json_boxes = enabled;
+        self
+    }
+
+    fn request_body(&self, image: &[u8]) -> Value {
+        let prompt = if self.json_boxes {
+            format!("{}\n{}", self.prompt, JSON_PROMPT)
+        } else {
+            self.prompt.clone()
+        };
+        let data_url = format!(
+            "data:{};base64,{}",
+            sniff_mime(image),
+            general_purpose::STANDARD.encode(image)
+        );
+
+        json!({
+            "model": self.model,
+            "max_tokens": self.max_tokens,
+            "temperature": 0,
+            "messages": [{
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": prompt},
+                    {"type": "image_url", "image_url": {"url": data_url}}
+                ]
+            }]
+        })
+    }
+
+    fn parse_response(&self, response: &Value) -> Result<OcrResult> {
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            let message = error.get("message").unwrap_or(error);
+            return Err(RsOcrError::OcrApi(value_to_string(message)));
+        }
+
+        let content = response
+            .pointer("/choices/0/message/content")
+            .ok_or_else(|| RsOcrError::OcrApi("模型响应中没有内容".to_string()))?;
+        // content 可能是字符串，也可能是 [{"type": "text", "text": ..}] 数组
+        let content = match content {
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join(""),
+            other => value_to_string(other),
+        };
+
+        let blocks = self
+            .json_boxes
+            .then(|| parse_json_lines(&content))
+            .flatten()
+            .unwrap_or_else(|| parse_plain_lines(&content));
+        Ok(OcrResult {
+            blocks,
+            ..Default::default()
+        })
+    }
+
+    /// 模型列表地址，用于健康检查
+    fn models_url(&self) -> String {
+        let url = self.client.url().trim_end_matches('/');
+        match url.strip_suffix("/chat/completions") {
+            Some(base) => format!("{}/models", base),
+            None => url.to_string(),
+        }
+    }
+}
+
+/// 按行拆分纯文本回复，去掉模型可能添加的代码块标记
+fn parse_plain_lines(content: &str) -> Vec<TextBlock> {
+    content
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with("```"))
+        .map(|line| TextBlock {
+            text: line.to_string(),
+            score: 1.0,
+            bbox: Default::default(),
+            end: "\n".to_string(),
+        })
+        .collect()
+}
+
+/// 解析JSON回复，允许前后有多余的文字或代码块标记
+fn parse_json_lines(content: &str) -> Option<Vec<TextBlock>> {
+    let start = content.find('{')?;
+    let end = content.rfind('}')?;
+    let value: Value = serde_json::from_str(content.get(start..=end)?).ok()?;
+    let lines = JsonPath::parse("$.lines[*]").ok()?.select(&value);
+    if lines.is_empty() && value.get("lines").is_none() {
+        return None;
+    }
+
+    Some(
+        lines
+            .into_iter()
+            .filter_map(|line| {
+                let text = match line {
+                    Value::String(s) => s.clone(),
+                    other => other.get("text").map(value_to_string)?,
+                };
+                let bbox = line
+                    .get("box")
+                    .and_then(|b| {
+                        BoxFormat::Xyxy
+                            .parse(b)
+                            .or_else(|| BoxFormat::Points.parse(b))
+                    })
+                    .unwrap_or_default();
+                let score = line
+                    .get("confidence")
+                    .and_then(Value::as_f64)
+                    .unwrap_or(1.0);
+                (!text.is_empty()).then(|| TextBlock {
+                    text,
+                    score,
+                    bbox,
+                    end: "\n".to_string(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// 按文件头判断图片的MIME类型，无法判断时按PNG处理
+fn sniff_mime(image: &[u8]) -> &'static str {
+    image_format::sniff(image).map_or("image/png", |kind| kind.mime_type())
+}
+
+impl OcrBackend for LlmBackend {
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+
+    fn endpoint(&self) -> &str {
+        self.client.url()
+    }
+
+    /// 识别参数由提示词控制，`options` 会被忽略
+    fn recognize_bytes(&self, image: &[u8], _options: &OcrOptions) -> Result<OcrResult> {
+        let body = self.request_body(image);
+        let response = retry::run(
+            self.client.retry_policy(),
+            self.client.circuit_breaker(),
+            || {
+                let response = self
+                    .client
+                    .http()
+                    .post(self.client.url())
+                    .json(&body)
+                    .send();
+                Outcome::classify(self.client.retry_policy(), response, |r| {
+                    r.status().as_u16()
+                })
+            },
+        )?;
+        check_status(response.status())?;
+
+        let json: Value = response.json().map_err(RsOcrError::Http)?;
+        self.parse_response(&json)
+    }
+
+    /// 查询模型列表（`/v1/models`）
+    fn health(&self) -> Result<()> {
+        let response = self
+            .client
+            .http()
+            .get(self.models_url())
+            .send()
+            .map_err(RsOcrError::Http)?;
+        check_status(response.status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    fn completion(content: &str) -> MockResponse {
+        MockResponse::json(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": content}}]
+        }))
+    }
+
+    #[test]
+    fn test_plain_transcription() -> Result<()> {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/v1/models" => MockResponse::json(json!({"data": [{"id": "llava"}]})),
+            _ => completion("```\n第一行\n\nSecond line  \n```"),
+        });
+        let client = OcrClient::new(format!("{}/v1/chat/completions", server.url()))?;
+        let backend = LlmBackend::new(client, "llava")
+            .prompt("transcribe")
+            .max_tokens(256);
+
+        backend.health()?;
+        let result = backend.recognize_bytes(b"\xFF\xD8\xFFjpeg", &OcrOptions::default())?;
+        assert_eq!(result.text(), "第一行\nSecond line");
+
+        let request = &server.requests()[1];
+        let body = request.json();
+        assert_eq!(body["model"], "llava");
+        assert_eq!(body["max_tokens"], 256);
+        assert_eq!(body["messages"][0]["content"][0]["text"], "transcribe");
+        assert_eq!(
+            body["messages"][0]["content"][1]["image_url"]["url"],
+            "data:image/jpeg;base64,/9j/anBlZw=="
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_boxes() -> Result<()> {
+        let server = MockServer::start(|_| {
+            completion(
+                "Sure:\n```json\n{\"lines\": [{\"text\": \"Total 42\", \"box\": [10, 20, 110, 40]}, {\"text\": \"\"}]}\n```",
+            )
+        });
+        let client = OcrClient::new(format!("{}/v1/chat/completions", server.url()))?;
+        let backend = LlmBackend::new(client, "qwen").json_boxes(true);
+
+        let result = backend.recognize_bytes(b"png", &OcrOptions::default())?;
+        assert_eq!(result.blocks.len(), 1);
+        assert_eq!(result.blocks[0].text, "Total 42");
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 20], [110, 20], [110, 40], [10, 40]]
+        );
+        let prompt = server.requests()[0].json()["messages"][0]["content"][0]["text"].clone();
+        assert!(prompt.as_str().unwrap().contains("\"lines\""));
+
+        // 模型没有按要求输出JSON时按行拆分
+        assert_eq!(
+            backend
+                .parse_response(&json!({"choices": [{"message": {"content": "a\nb"}}]}))?
+                .text(),
+            "a\nb"
+        );
+        let err = backend
+            .parse_response(&json!({"error": {"message": "model not found"}}))
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrApi(ref m) if m == "model not found"));
+        Ok(())
+    }
+}
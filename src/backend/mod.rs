@@ -0,0 +1,357 @@
+//! 可插拔的OCR后端
+//!
+//! [`OcrBackend`] 把“图片字节 + 识别选项”转换为结构化的 [`OcrResult`]，
+//! 命令行、图形界面和库代码都只依赖这个trait。具体使用哪个后端由配置文件中的
+//! `backend` 项（或命令行的 `--backend` 参数）按名称选择，见 [`create_backend`]。
+
+mod baidu;
+mod google;
+mod http;
+pub mod json_path;
+mod llm;
+mod paddle;
+mod preprocess;
+mod tesseract;
+mod umi;
+
+pub use baidu::{BaiduBackend, BaiduModel, DEFAULT_BAIDU_URL};
+pub use google::{DEFAULT_GOOGLE_URL, GoogleFeature, GoogleVisionBackend};
+pub use http::{BodyKind, DEFAULT_TEMPLATE, HttpBackend, ResponseMapping};
+pub use llm::{DEFAULT_LLM_MAX_TOKENS, DEFAULT_LLM_PROMPT, DEFAULT_LLM_URL, LlmBackend};
+pub use paddle::{DEFAULT_PADDLE_URL, PaddleBackend};
+pub use preprocess::PreprocessBackend;
+pub use tesseract::{
+    DEFAULT_TESSERACT, DEFAULT_TESSERACT_TIMEOUT, TesseractBackend, TesseractFormat,
+};
+
+use crate::capabilities::ServerCapabilities;
+use crate::config::Config;
+use crate::image_format;
+use crate::models::{OcrOptions, OcrResult, PageResult, Result, RsOcrError};
+use crate::pages::{self, PageRange};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Debug;
+
+/// 默认的后端名称
+pub const DEFAULT_BACKEND: &str = "umi";
+
+/// 可用的后端名称
+pub const BACKENDS: &[&str] = &[
+    "umi",
+    "tesseract",
+    "http",
+    "paddle",
+    "llm",
+    "google",
+    "baidu",
+];
+
+/// OCR后端
+///
+/// 实现者至少需要提供 [`name`](OcrBackend::name)、[`endpoint`](OcrBackend::endpoint)、
+/// [`recognize_bytes`](OcrBackend::recognize_bytes) 和 [`health`](OcrBackend::health)，
+/// 其余方法都有默认实现。
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::{Config, OcrBackend, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let backend = rsocr::create_backend(&Config::load(), None)?;
+///     let result = backend.recognize_path("image.png", &OcrOptions::default())?;
+///     println!("[{}] {}", backend.name(), result.text());
+///     Ok(())
+/// }
+/// ```
+pub trait OcrBackend: Debug + Send + Sync {
+    /// 后端名称，与配置文件中的 `backend` 项对应
+    fn name(&self) -> &'static str;
+
+    /// 后端地址（URL或可执行文件路径），用于提示信息
+    fn endpoint(&self) -> &str;
+
+    /// 识别图片
+    ///
+    /// # 参数
+    /// * `image` - 图片文件的原始字节
+    /// * `options` - OCR请求选项，后端不支持的选项会被忽略
+    ///
+    /// # 返回
+    /// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+    fn recognize_bytes(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult>;
+
+    /// 识别图片文件，默认读取文件后调用 [`recognize_bytes`](OcrBackend::recognize_bytes)
+    fn recognize_path(&self, image_path: &str, options: &OcrOptions) -> Result<OcrResult> {
+        let image = std::fs::read(image_path).map_err(RsOcrError::Io)?;
+        self.recognize_bytes(&image, options)
+    }
+
+    /// 识别多页图片的指定页
+    ///
+    /// 多页TIFF和GIF动画按页拆分后逐页调用 [`recognize_bytes`](OcrBackend::recognize_bytes)，
+    /// 其他图片视为只有一页，见 [`pages::split_pages`]。
+    ///
+    /// # 参数
+    /// * `image` - 图片文件的原始字节
+    /// * `options` - OCR请求选项
+    /// * `pages` - 要识别的页码范围
+    ///
+    /// # 返回
+    /// * `Result<Vec<PageResult>>` - 按页码顺序排列的识别结果，任意一页失败时返回错误；
+    ///   没有页面落在范围内时返回 [`RsOcrError::Config`]
+    fn recognize_pages(
+        &self,
+        image: &[u8],
+        options: &OcrOptions,
+        pages: &PageRange,
+    ) -> Result<Vec<PageResult>> {
+        pages::split_pages(image, pages)?
+            .into_iter()
+            .map(|page| {
+                Ok(PageResult {
+                    page: page.number,
+                    result: self.recognize_bytes(&page.data, options)?,
+                })
+            })
+            .collect()
+    }
+
+    /// 识别图片文件的指定页
+    ///
+    /// 不可能有多页的格式交给 [`recognize_path`](OcrBackend::recognize_path)，
+    /// 否则读取文件后调用 [`recognize_pages`](OcrBackend::recognize_pages)。
+    fn recognize_path_pages(
+        &self,
+        image_path: &str,
+        options: &OcrOptions,
+        pages: &PageRange,
+    ) -> Result<Vec<PageResult>> {
+        if image_format::sniff_file(image_path)?.is_some_and(|kind| kind.can_have_pages()) {
+            let image = std::fs::read(image_path).map_err(RsOcrError::Io)?;
+            return self.recognize_pages(&image, options, pages);
+        }
+        pages.check(1)?;
+        Ok(vec![PageResult {
+            page: 1,
+            result: self.recognize_path(image_path, options)?,
+        }])
+    }
+
+    /// 检查后端是否可用
+    fn health(&self) -> Result<()>;
+
+    /// 发送请求前校验OCR请求选项，默认不做校验
+    fn validate_options(&self, _options: &OcrOptions) -> Result<()> {
+        Ok(())
+    }
+
+    /// 查询后端支持的参数，默认返回空列表（不限制）
+    fn capabilities(&self) -> Result<ServerCapabilities> {
+        Ok(ServerCapabilities::default())
+    }
+}
+
+/// 服务返回的坐标格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoxFormat {
+    /// 四个顶点 `[[x, y], ...]` 或 `[{"x": .., "y": ..}, ...]`，多于四个点时取外接矩形
+    #[default]
+    Points,
+    /// 左上角和右下角 `[x0, y0, x1, y1]`
+    Xyxy,
+    /// 左上角和宽高 `[x, y, w, h]` 或 `{"left"/"x", "top"/"y", "width", "height"}`
+    Xywh,
+}
+
+impl BoxFormat {
+    /// 把坐标转换为 [`TextBlock::bbox`](crate::TextBlock::bbox) 的四点格式
+    pub fn parse(self, value: &Value) -> Option<[[i32; 2]; 4]> {
+        let number = |v: &Value| v.as_f64().map(|n| n.round() as i32);
+        match self {
+            Self::Points => {
+                let points: Vec<[i32; 2]> = value
+                    .as_array()?
+                    .iter()
+                    .map(|p| match p {
+                        Value::Array(xy) if xy.len() >= 2 => {
+                            Some([number(&xy[0])?, number(&xy[1])?])
+                        }
+                        Value::Object(map) => {
+                            Some([number(map.get("x")?)?, number(map.get("y")?)?])
+                        }
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?;
+                match points.len() {
+                    4 => Some([points[0], points[1], points[2], points[3]]),
+                    0 => None,
+                    _ => {
+                        let (l, t) = points
+                            .iter()
+                            .fold((i32::MAX, i32::MAX), |(l, t), p| (l.min(p[0]), t.min(p[1])));
+                        let (r, b) = points
+                            .iter()
+                            .fold((i32::MIN, i32::MIN), |(r, b), p| (r.max(p[0]), b.max(p[1])));
+                        Some(rect_box(l, t, r, b))
+                    }
+                }
+            }
+            Self::Xyxy => {
+                let v = value.as_array()?;
+                (v.len() >= 4).then_some(())?;
+                Some(rect_box(
+                    number(&v[0])?,
+                    number(&v[1])?,
+                    number(&v[2])?,
+                    number(&v[3])?,
+                ))
+            }
+            Self::Xywh => {
+                let [x, y, w, h] = match value {
+                    Value::Array(v) if v.len() >= 4 => [
+                        number(&v[0])?,
+                        number(&v[1])?,
+                        number(&v[2])?,
+                        number(&v[3])?,
+                    ],
+                    Value::Object(map) => {
+                        let field = |names: &[&str]| {
+                            names.iter().find_map(|n| map.get(*n)).and_then(number)
+                        };
+                        [
+                            field(&["left", "x"])?,
+                            field(&["top", "y"])?,
+                            field(&["width", "w"])?,
+                            field(&["height", "h"])?,
+                        ]
+                    }
+                    _ => return None,
+                };
+                Some(rect_box(x, y, x + w, y + h))
+            }
+        }
+    }
+}
+
+/// 由左上角和右下角构造四点坐标（顺时针，从左上角开始）
+pub(crate) fn rect_box(left: i32, top: i32, right: i32, bottom: i32) -> [[i32; 2]; 4] {
+    [[left, top], [right, top], [right, bottom], [left, bottom]]
+}
+
+/// 根据配置创建OCR后端
+///
+/// # 参数
+/// * `config` - 配置，`config.backend` 为空时使用 [`DEFAULT_BACKEND`]
+/// * `args_url` - 命令行提供的URL，优先于配置文件（只对HTTP后端有效）
+///
+/// # 返回
+/// * `Result<Box<dyn OcrBackend>>` - 后端名称未知时返回 [`RsOcrError::Config`]
+pub fn create_backend(config: &Config, args_url: Option<String>) -> Result<Box<dyn OcrBackend>> {
+    let backend: Box<dyn OcrBackend> = match config.backend_name() {
+        "umi" | "umi-ocr" => Box::new(config.client_builder(args_url)?.build()?),
+        "tesseract" => Box::new(config.tesseract.to_backend()),
+        "http" => Box::new(config.http_backend(args_url)?),
+        "paddle" | "paddleocr" => Box::new(config.paddle_backend(args_url)?),
+        "llm" => Box::new(config.llm_backend(args_url)?),
+        "google" => Box::new(config.google_backend(args_url)?),
+        "baidu" => Box::new(config.baidu_backend(args_url)?),
+        other => {
+            return Err(RsOcrError::Config(format!(
+                "未知的OCR后端: {}（可用: {}）",
+                other,
+                BACKENDS.join(", ")
+            )));
+        }
+    };
+
+    // 上传前检查图片格式，并按配置执行预处理
+    Ok(Box::new(PreprocessBackend::new(
+        backend,
+        config.preprocess.to_pipeline(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_box_format() {
+        let quad = [[1, 2], [11, 2], [11, 12], [1, 12]];
+        assert_eq!(BoxFormat::Points.parse(&json!(quad)), Some(quad));
+        assert_eq!(
+            BoxFormat::Points.parse(&json!([{"x": 1, "y": 2}, {"x": 11.2, "y": 2}, {"x": 11, "y": 12}, {"x": 1, "y": 12}])),
+            Some(quad)
+        );
+        assert_eq!(
+            BoxFormat::Points.parse(&json!([[1, 2], [6, 3], [11, 2], [11, 12], [1, 12]])),
+            Some(quad)
+        );
+        assert_eq!(BoxFormat::Xyxy.parse(&json!([1, 2, 11, 12])), Some(quad));
+        assert_eq!(BoxFormat::Xywh.parse(&json!([1, 2, 10, 10])), Some(quad));
+        assert_eq!(
+            BoxFormat::Xywh.parse(&json!({"left": 1, "top": 2, "width": 10, "height": 10})),
+            Some(quad)
+        );
+        assert_eq!(BoxFormat::Xyxy.parse(&json!("bad")), None);
+    }
+
+    #[test]
+    fn test_create_backend() -> Result<()> {
+        let mut config = Config::default();
+        let backend = create_backend(&config, Some("http://127.0.0.1:1/api/ocr".to_string()))?;
+        assert_eq!(backend.name(), "umi");
+        assert_eq!(backend.endpoint(), "http://127.0.0.1:1/api/ocr");
+
+        config.set("backend", "tesseract")?;
+        config.set("tesseract.path", "/opt/tesseract")?;
+        let backend = create_backend(&config, None)?;
+        assert_eq!(backend.name(), "tesseract");
+        assert_eq!(backend.endpoint(), "/opt/tesseract");
+
+        config.set("backend", "paddle")?;
+        let backend = create_backend(&config, None)?;
+        assert_eq!(backend.name(), "paddle");
+        assert_eq!(backend.endpoint(), DEFAULT_PADDLE_URL);
+
+        config.set("backend", "llm")?;
+        assert!(matches!(
+            create_backend(&config, None),
+            Err(RsOcrError::Config(_))
+        ));
+        config.set("llm.model", "llava")?;
+        let backend = create_backend(&config, None)?;
+        assert_eq!(backend.name(), "llm");
+        assert_eq!(backend.endpoint(), DEFAULT_LLM_URL);
+
+        config.set("backend", "google")?;
+        config.set("google.api_key", "key")?;
+        let backend = create_backend(&config, None)?;
+        assert_eq!(backend.name(), "google");
+        assert_eq!(
+            backend.endpoint(),
+            "https://vision.googleapis.com/v1/images:annotate"
+        );
+
+        config.set("backend", "baidu")?;
+        assert!(create_backend(&config, None).is_err());
+        config.set("baidu.access_token", "token")?;
+        config.set("baidu.model", "accurate")?;
+        let backend = create_backend(&config, None)?;
+        assert_eq!(backend.name(), "baidu");
+        assert_eq!(
+            backend.endpoint(),
+            "https://aip.baidubce.com/rest/2.0/ocr/v1/accurate"
+        );
+
+        config.set("backend", "nope")?;
+        let err = create_backend(&config, None).unwrap_err();
+        assert!(matches!(err, RsOcrError::Config(_)));
+        assert!(err.to_string().contains("umi"));
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+//! PaddleOCR（PaddleHub Serving）后端
+//!
+//! 对应 `hub serving start -m ch_pp-ocrv3` 等命令启动的服务：请求体为
+//! `{"images": [base64, ...]}`，响应中的 `results` 与请求的图片一一对应，
+//! 每个文本块包含 `text`、`confidence` 和 `text_region`（四个顶点）。
+//! `status` 不为 `"000"` 时返回 [`RsOcrError::OcrServer`]。
+
+use super::{BoxFormat, OcrBackend};
+use crate::client::{OcrClient, check_status};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use crate::retry::{self, Outcome};
+use base64::{Engine as _, engine::general_purpose};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 默认的服务地址
+pub const DEFAULT_PADDLE_URL: &str = "http://127.0.0.1:8866/predict/ocr_system";
+
+/// 表示成功的 `status`
+const STATUS_SUCCESS: &str = "000";
+
+#[derive(Debug, Deserialize)]
+struct PaddleResponse {
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    status: Value,
+    #[serde(default)]
+    results: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaddleBlock {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    confidence: f64,
+    #[serde(default)]
+    text_region: Value,
+}
+
+impl PaddleResponse {
+    fn into_results(self, expected: usize) -> Result<Vec<OcrResult>> {
+        let status = match &self.status {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if status != STATUS_SUCCESS {
+            return Err(RsOcrError::OcrServer {
+                code: status.parse().unwrap_or(-1),
+                message: self.msg,
+            });
+        }
+
+        let pages: Vec<Vec<PaddleBlock>> = serde_json::from_value(self.results)
+            .map_err(|e| RsOcrError::OcrApi(format!("无法解析PaddleOCR结果: {}", e)))?;
+        if pages.len() != expected {
+            return Err(RsOcrError::OcrApi(format!(
+                "PaddleOCR返回了 {} 个结果，请求了 {} 张图片",
+                pages.len(),
+                expected
+            )));
+        }
+
+        Ok(pages
+            .into_iter()
+            .map(|blocks| OcrResult {
+                blocks: blocks
+                    .into_iter()
+                    .map(|block| TextBlock {
+                        text: block.text,
+                        score: block.confidence,
+                        bbox: BoxFormat::Points
+                            .parse(&block.text_region)
+                            .unwrap_or_default(),
+                        end: "\n".to_string(),
+                    })
+                    .collect(),
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+/// PaddleOCR（PaddleHub Serving）后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::PaddleBackend;
+/// use rsocr::OcrClient;
+///
+/// fn main() -> rsocr::Result<()> {
+///     let backend = PaddleBackend::new(OcrClient::new("http://127.0.0.1:8866/predict/ocr_system")?);
+///     let a = std::fs::read("a.png")?;
+///     let b = std::fs::read("b.png")?;
+///     for result in backend.recognize_batch(&[&a, &b])? {
+///         println!("{}", result.text());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PaddleBackend {
+    client: OcrClient,
+}
+
+impl PaddleBackend {
+    /// 创建后端
+    ///
+    /// # 参数
+    /// * `client` - 提供URL、超时、请求头和重试策略的客户端
+    pub fn new(client: OcrClient) -> Self {
+        Self { client }
+    }
+
+    /// 在一个请求中识别多张图片
+    ///
+    /// # 参数
+    /// * `images` - 图片文件的原始字节
+    ///
+    /// # 返回
+    /// * `Result<Vec<OcrResult>>` - 与 `images` 顺序一致的识别结果
+    pub fn recognize_batch(&self, images: &[&[u8]]) -> Result<Vec<OcrResult>> {
+        let body = serde_json::json!({
+            "images": images
+                .iter()
+                .map(|image| general_purpose::STANDARD.encode(image))
+                .collect::<Vec<_>>()
+        });
+
+        let response = retry::run(
+            self.client.retry_policy(),
+            self.client.circuit_breaker(),
+            || {
+                let response = self
+                    .client
+                    .http()
+                    .post(self.client.url())
+                    .json(&body)
+                    .send();
+                Outcome::classify(self.client.retry_policy(), response, |r| {
+                    r.status().as_u16()
+                })
+            },
+        )?;
+        check_status(response.status())?;
+
+        let response: PaddleResponse = response.json().map_err(RsOcrError::Http)?;
+        response.into_results(images.len())
+    }
+}
+
+impl OcrBackend for PaddleBackend {
+    fn name(&self) -> &'static str {
+        "paddle"
+    }
+
+    fn endpoint(&self) -> &str {
+        self.client.url()
+    }
+
+    /// PaddleHub Serving 不支持逐次请求的识别参数，`options` 会被忽略
+    fn recognize_bytes(&self, image: &[u8], _options: &OcrOptions) -> Result<OcrResult> {
+        Ok(self.recognize_batch(&[image])?.remove(0))
+    }
+
+    fn health(&self) -> Result<()> {
+        self.client.health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    const FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/paddlehub/ocr_system.json"
+    ));
+    const ERROR_FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/paddlehub/error.json"
+    ));
+
+    fn fixture(text: &str) -> MockResponse {
+        MockResponse::json(serde_json::from_str(text).unwrap())
+    }
+
+    #[test]
+    fn test_recognize_batch() -> Result<()> {
+        let server = MockServer::start(|_| fixture(FIXTURE));
+        let backend = PaddleBackend::new(OcrClient::new(format!(
+            "{}/predict/ocr_system",
+            server.url()
+        ))?);
+
+        let results = backend.recognize_batch(&[b"first", b"second"])?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].text(), "飞桨文字识别\nPaddleOCR 2.7");
+        assert_eq!(results[0].blocks[0].score, 0.9975);
+        assert_eq!(
+            results[0].blocks[1].bbox,
+            [[25, 60], [198, 61], [198, 90], [25, 89]]
+        );
+        assert!(results[1].is_empty());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/predict/ocr_system");
+        assert_eq!(
+            request.json(),
+            serde_json::json!({"images": ["Zmlyc3Q=", "c2Vjb25k"]})
+        );
+
+        // 单张图片与请求数量不一致时报错
+        let err = backend
+            .recognize_bytes(b"first", &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrApi(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_status() -> Result<()> {
+        let server = MockServer::start(|_| fixture(ERROR_FIXTURE));
+        let backend = PaddleBackend::new(OcrClient::new(server.url())?);
+
+        let err = backend
+            .recognize_bytes(b"x", &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RsOcrError::OcrServer { code: -1, ref message } if message == "Please check data format!"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+//! 在任意后端之前检查图片格式并执行预处理
+
+use super::OcrBackend;
+use crate::capabilities::ServerCapabilities;
+use crate::image_format;
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError};
+use crate::preprocess::{self, Pipeline, ProcessedImage, ROTATIONS};
+use image::metadata::Orientation;
+
+/// 先按 [`Pipeline`] 预处理图片，再交给内部后端识别
+///
+/// 不是图片的数据不会发送给后端；TIFF、WebP等格式转换为PNG后上传，
+/// 带有EXIF方向的照片转正后上传。识别结果中的文本框坐标会换算回转正后原图的像素坐标。
+/// 开启 [`Pipeline::auto_rotate`] 时，选用的旋转角度记录在 [`OcrResult::rotation`] 中。
+/// [`create_backend`](super::create_backend) 会用 `[preprocess]` 配置包装所选的后端。
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::PreprocessBackend;
+/// use rsocr::preprocess::{Pipeline, PreprocessStep};
+/// use rsocr::{OcrBackend, OcrClient, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::new("http://127.0.0.1:1224/api/ocr")?;
+///     let backend = PreprocessBackend::new(
+///         Box::new(client),
+///         Pipeline::new([PreprocessStep::Grayscale, PreprocessStep::Upscale]),
+///     );
+///     let result = backend.recognize_path("screenshot.png", &OcrOptions::default())?;
+///     println!("{}", result.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PreprocessBackend {
+    inner: Box<dyn OcrBackend>,
+    pipeline: Pipeline,
+}
+
+impl PreprocessBackend {
+    /// 包装后端
+    ///
+    /// # 参数
+    /// * `inner` - 实际执行识别的后端
+    /// * `pipeline` - 预处理流程
+    pub fn new(inner: Box<dyn OcrBackend>, pipeline: Pipeline) -> Self {
+        Self { inner, pipeline }
+    }
+
+    /// 预处理流程
+    pub fn pipeline(&self) -> &Pipeline {
+        &self.pipeline
+    }
+
+    /// 识别预处理后的图片，把坐标换算回预处理前
+    fn recognize_processed(
+        &self,
+        processed: ProcessedImage,
+        options: &OcrOptions,
+    ) -> Result<OcrResult> {
+        let mut result = self.inner.recognize_bytes(&processed.data, options)?;
+        result.scale_boxes(1.0 / processed.scale_x, 1.0 / processed.scale_y);
+        Ok(result)
+    }
+
+    /// 依次识别旋转 [`ROTATIONS`] 中各角度的图片，保留平均置信度最高的结果
+    ///
+    /// 平均置信度相同时保留旋转角度较小的结果。
+    fn recognize_rotations(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult> {
+        let kind = image_format::detect(image)?;
+        let upright = preprocess::decode(image)?;
+        let (width, height) = (upright.width(), upright.height());
+
+        let mut best = self.recognize_processed(self.pipeline.process(image)?, options)?;
+        best.rotation = Some(0);
+        for rotation in ROTATIONS.into_iter().filter(|&r| r != 0) {
+            let rotated = self
+                .pipeline
+                .process_image(preprocess::rotate(&upright, rotation), kind)?;
+            let mut result = self.recognize_processed(rotated, options)?;
+            result.unrotate_boxes(rotation, width, height);
+            log::debug!(
+                "旋转 {}° 的平均置信度: {:.3}",
+                rotation,
+                result.mean_score()
+            );
+            if result.mean_score() > best.mean_score() {
+                result.rotation = Some(rotation);
+                best = result;
+            }
+        }
+        Ok(best)
+    }
+}
+
+impl OcrBackend for PreprocessBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn endpoint(&self) -> &str {
+        self.inner.endpoint()
+    }
+
+    fn recognize_bytes(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult> {
+        if self.pipeline.is_auto_rotate() {
+            return self.recognize_rotations(image, options);
+        }
+        self.recognize_processed(self.pipeline.process(image)?, options)
+    }
+
+    fn recognize_path(&self, image_path: &str, options: &OcrOptions) -> Result<OcrResult> {
+        // 不需要处理的图片交给内部后端读取，保留流式上传
+        let supported =
+            image_format::sniff_file(image_path)?.is_some_and(|k| k.is_widely_supported());
+        if self.pipeline.is_empty()
+            && supported
+            && image_format::orientation_file(image_path)? == Orientation::NoTransforms
+        {
+            return self.inner.recognize_path(image_path, options);
+        }
+        let image = std::fs::read(image_path).map_err(RsOcrError::Io)?;
+        self.recognize_bytes(&image, options)
+    }
+
+    fn health(&self) -> Result<()> {
+        self.inner.health()
+    }
+
+    fn validate_options(&self, options: &OcrOptions) -> Result<()> {
+        self.inner.validate_options(options)
+    }
+
+    fn capabilities(&self) -> Result<ServerCapabilities> {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::OcrClient;
+    use crate::pages::PageRange;
+    use crate::preprocess::PreprocessStep;
+    use crate::test_support::{MockResponse, MockServer};
+    use base64::{Engine as _, engine::general_purpose};
+    use image::{DynamicImage, GrayImage, ImageFormat, Luma};
+
+    #[test]
+    fn test_boxes_in_source_pixels() -> Result<()> {
+        let server = MockServer::start(|_| {
+            MockResponse::umi_block("ok", 0.9, [[20, 10], [60, 10], [60, 30], [20, 30]])
+        });
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(
+            Box::new(client),
+            Pipeline::new([PreprocessStep::Upscale]).upscale_factor(2.0),
+        );
+        assert_eq!(backend.name(), "umi");
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(GrayImage::from_pixel(40, 20, Luma([200])))
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let result = backend.recognize_bytes(png.get_ref(), &OcrOptions::default())?;
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 5], [30, 5], [30, 15], [10, 15]]
+        );
+
+        let sent = general_purpose::STANDARD
+            .decode(server.requests()[0].json()["base64"].as_str().unwrap())?;
+        let sent = image::load_from_memory(&sent).unwrap();
+        assert_eq!((sent.width(), sent.height()), (80, 40));
+
+        // 缩小后上传，坐标同样换算回原图
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(Box::new(client), Pipeline::new([]).max_side(10));
+        let result = backend.recognize_bytes(png.get_ref(), &OcrOptions::default())?;
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[80, 40], [240, 40], [240, 120], [80, 120]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_checks_format() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(Box::new(client), Pipeline::default());
+        let dir = tempfile::tempdir()?;
+
+        // TIFF转换为PNG后上传
+        let image = DynamicImage::ImageLuma8(GrayImage::from_pixel(8, 8, Luma([200])));
+        let tiff = dir.path().join("scan.tiff");
+        image.save_with_format(&tiff, ImageFormat::Tiff).unwrap();
+        backend.recognize_path(tiff.to_str().unwrap(), &OcrOptions::default())?;
+        // PNG原样上传
+        let png = dir.path().join("scan.png");
+        image.save_with_format(&png, ImageFormat::Png).unwrap();
+        backend.recognize_path(png.to_str().unwrap(), &OcrOptions::default())?;
+
+        let requests = server.requests();
+        let sent =
+            general_purpose::STANDARD.decode(requests[0].json()["base64"].as_str().unwrap())?;
+        assert!(sent.starts_with(b"\x89PNG"));
+        let sent =
+            general_purpose::STANDARD.decode(requests[1].json()["base64"].as_str().unwrap())?;
+        assert_eq!(sent, std::fs::read(&png)?);
+
+        // 不是图片时不发送请求
+        let text = dir.path().join("notes.png");
+        std::fs::write(&text, "just text")?;
+        let err = backend
+            .recognize_path(text.to_str().unwrap(), &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(_)));
+        let err = backend
+            .recognize_bytes(b"just text", &OcrOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(_)));
+        assert_eq!(server.requests().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recognize_pages() -> Result<()> {
+        let server = MockServer::start(|request| {
+            // 返回上传图片的宽度，用于区分页面
+            let image = general_purpose::STANDARD
+                .decode(request.json()["base64"].as_str().unwrap())
+                .unwrap();
+            let width = image::load_from_memory(&image).unwrap().width();
+            MockResponse::umi_text(&width.to_string(), 1.0)
+        });
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(Box::new(client), Pipeline::default());
+
+        let dir = tempfile::tempdir()?;
+        let fax = dir.path().join("fax.tiff");
+        let mut encoder = tiff::encoder::TiffEncoder::new(std::fs::File::create(&fax)?).unwrap();
+        for width in [3, 5, 7] {
+            let pixels = vec![255; width as usize * 2];
+            encoder
+                .write_image::<tiff::encoder::colortype::Gray8>(width, 2, &pixels)
+                .unwrap();
+        }
+        drop(encoder);
+
+        let fax = fax.to_str().unwrap();
+        let pages = backend.recognize_path_pages(fax, &OcrOptions::default(), &PageRange::all())?;
+        let texts: Vec<(usize, String)> = pages.iter().map(|p| (p.page, p.result.text())).collect();
+        assert_eq!(
+            texts,
+            [
+                (1, "3".to_string()),
+                (2, "5".to_string()),
+                (3, "7".to_string())
+            ]
+        );
+        let range = "2".parse()?;
+        let pages = backend.recognize_path_pages(fax, &OcrOptions::default(), &range)?;
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].page, 2);
+        assert_eq!(server.requests().len(), 4);
+
+        // 单页图片只有第1页
+        let png = dir.path().join("page.png");
+        DynamicImage::ImageLuma8(GrayImage::from_pixel(4, 4, Luma([0])))
+            .save_with_format(&png, ImageFormat::Png)
+            .unwrap();
+        let err = backend
+            .recognize_path_pages(png.to_str().unwrap(), &OcrOptions::default(), &range)
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::Config(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_rotate() -> Result<()> {
+        // 只有图片横放（宽大于高）时才“识别”出文字
+        let server = MockServer::start(|request| {
+            let image = general_purpose::STANDARD
+                .decode(request.json()["base64"].as_str().unwrap())
+                .unwrap();
+            let image = image::load_from_memory(&image).unwrap();
+            let score = if image.width() > image.height() {
+                0.95
+            } else {
+                0.3
+            };
+            MockResponse::umi_block("字", score, [[10, 5], [30, 5], [30, 15], [10, 15]])
+        });
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(Box::new(client), Pipeline::new([]).auto_rotate(true));
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(GrayImage::from_pixel(40, 100, Luma([200])))
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let result = backend.recognize_bytes(png.get_ref(), &OcrOptions::default())?;
+        assert_eq!(server.requests().len(), 4);
+        // 90° 和 270° 的得分相同，保留先尝试的 90°
+        assert_eq!(result.rotation, Some(90));
+        assert_eq!(result.blocks[0].score, 0.95);
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[5, 90], [5, 70], [15, 70], [15, 90]]
+        );
+
+        // EXIF方向在上传前应用，不需要自动检测
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend = PreprocessBackend::new(Box::new(client), Pipeline::default());
+        let dir = tempfile::tempdir()?;
+        let photo = dir.path().join("photo.jpg");
+        let exif = crate::test_support::exif_jpeg(20, 50, Orientation::Rotate270);
+        std::fs::write(&photo, exif)?;
+        let result = backend.recognize_path(photo.to_str().unwrap(), &OcrOptions::default())?;
+        assert_eq!(result.blocks[0].score, 0.95);
+        assert_eq!(result.rotation, None);
+        Ok(())
+    }
+}
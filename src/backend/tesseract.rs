@@ -0,0 +1,629 @@
+//! Tesseract命令行后端
+//!
+//! 调用本机的 `tesseract` 可执行文件离线识别，解析其TSV或hOCR输出。
+//! 每一行文字对应一个 [`TextBlock`]，置信度为该行单词置信度的平均值（0 ~ 1）。
+
+use super::OcrBackend;
+use crate::capabilities::{OptionChoice, ServerCapabilities};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError, TextBlock};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 默认的可执行文件
+pub const DEFAULT_TESSERACT: &str = "tesseract";
+
+/// 默认的超时时间
+pub const DEFAULT_TESSERACT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Tesseract的输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TesseractFormat {
+    /// 制表符分隔的逐词结果
+    #[default]
+    Tsv,
+    /// HTML格式的hOCR结果
+    Hocr,
+}
+
+impl TesseractFormat {
+    fn config_name(self) -> &'static str {
+        match self {
+            Self::Tsv => "tsv",
+            Self::Hocr => "hocr",
+        }
+    }
+}
+
+/// Tesseract命令行后端
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::backend::TesseractBackend;
+/// use rsocr::{OcrBackend, OcrOptions};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let backend = TesseractBackend::new("tesseract")
+///         .languages(["chi_sim", "eng"])
+///         .psm(6);
+///     let result = backend.recognize_path("image.png", &OcrOptions::default())?;
+///     println!("{}", result.text());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TesseractBackend {
+    path: String,
+    languages: Vec<String>,
+    psm: Option<u8>,
+    timeout: Duration,
+    format: TesseractFormat,
+}
+
+impl TesseractBackend {
+    /// 创建后端
+    ///
+    /// # 参数
+    /// * `path` - `tesseract` 可执行文件的路径，在 `PATH` 中时可以只写名称
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            languages: Vec::new(),
+            psm: None,
+            timeout: DEFAULT_TESSERACT_TIMEOUT,
+            format: TesseractFormat::default(),
+        }
+    }
+
+    /// 设置识别语言，如 `["chi_sim", "eng"]`，为空时使用tesseract的默认语言
+    pub fn languages<I, S>(mut self, languages: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.languages = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 设置页面分割模式（`--psm`）
+    pub fn psm(mut self, psm: u8) -> Self {
+        self.psm = Some(psm);
+        self
+    }
+
+    /// 设置单次识别的超时时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置输出格式
+    pub fn format(mut self, format: TesseractFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// 识别参数：`options.language` 是Tesseract语言代码时优先于配置的语言列表
+    ///
+    /// `[ocr] language` 中常是Umi-OCR的模型配置路径（如 `models/config_chinese.txt`），
+    /// 这类值会被忽略。
+    fn recognize_args(&self, input: &str, options: &OcrOptions) -> Vec<String> {
+        let mut args = vec![input.to_string(), "stdout".to_string()];
+        let requested = options.language.as_deref().filter(|language| {
+            let valid = is_language_code(language);
+            if !valid {
+                log::debug!("忽略不是Tesseract语言代码的识别语言: {}", language);
+            }
+            valid
+        });
+        let language = requested
+            .map(str::to_string)
+            .or_else(|| (!self.languages.is_empty()).then(|| self.languages.join("+")));
+        if let Some(language) = language {
+            args.extend(["-l".to_string(), language]);
+        }
+        if let Some(psm) = self.psm {
+            args.extend(["--psm".to_string(), psm.to_string()]);
+        }
+        args.push(self.format.config_name().to_string());
+        args
+    }
+
+    fn parse(&self, output: &[u8]) -> Result<OcrResult> {
+        let output = String::from_utf8_lossy(output);
+        let blocks = match self.format {
+            TesseractFormat::Tsv => parse_tsv(&output)?,
+            TesseractFormat::Hocr => parse_hocr(&output),
+        };
+        Ok(OcrResult {
+            blocks,
+            ..Default::default()
+        })
+    }
+
+    /// 运行tesseract并返回标准输出，超时时结束进程
+    fn run(&self, args: &[String], input: Option<Vec<u8>>) -> Result<Vec<u8>> {
+        let started = Instant::now();
+        let mut child = Command::new(&self.path)
+            .args(args)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| RsOcrError::OcrApi(format!("无法运行 {}: {}", self.path, e)))?;
+
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            // tesseract可能在读完输入前退出，写入失败由退出状态反映
+            thread::spawn(move || {
+                let _ = stdin.write_all(&input);
+            });
+        }
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let status = wait_with_timeout(&mut child, self.timeout)?.ok_or_else(|| {
+            RsOcrError::OcrApi(format!("{} 运行超时（{:?}）", self.path, started.elapsed()))
+        })?;
+
+        let stdout = stdout.join().unwrap_or_default();
+        if !status.success() {
+            let stderr = stderr.join().unwrap_or_default();
+            return Err(RsOcrError::OcrApi(format!(
+                "{} 运行失败（{}）: {}",
+                self.path,
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        Ok(stdout)
+    }
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// 等待进程退出，超时时结束进程并返回 `None`
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().map_err(RsOcrError::Io)? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+impl OcrBackend for TesseractBackend {
+    fn name(&self) -> &'static str {
+        "tesseract"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.path
+    }
+
+    fn recognize_bytes(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult> {
+        let output = self.run(&self.recognize_args("stdin", options), Some(image.to_vec()))?;
+        self.parse(&output)
+    }
+
+    fn recognize_path(&self, image_path: &str, options: &OcrOptions) -> Result<OcrResult> {
+        if !std::path::Path::new(image_path).is_file() {
+            return Err(RsOcrError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("文件不存在: {}", image_path),
+            )));
+        }
+        let output = self.run(&self.recognize_args(image_path, options), None)?;
+        self.parse(&output)
+    }
+
+    fn health(&self) -> Result<()> {
+        self.run(&["--version".to_string()], None).map(|_| ())
+    }
+
+    /// 通过 `--list-langs` 查询已安装的语言
+    fn capabilities(&self) -> Result<ServerCapabilities> {
+        let output = self.run(&["--list-langs".to_string()], None)?;
+        let languages = String::from_utf8_lossy(&output)
+            .lines()
+            .skip(1)
+            .map(str::trim)
+            .filter(|lang| !lang.is_empty())
+            .map(|lang| OptionChoice {
+                value: lang.to_string(),
+                label: lang.to_string(),
+            })
+            .collect();
+        Ok(ServerCapabilities {
+            languages,
+            ..Default::default()
+        })
+    }
+}
+
+/// 正在合并的一行文字
+#[derive(Default)]
+struct LineBuilder {
+    words: Vec<String>,
+    confidences: Vec<f64>,
+    rect: Option<[i32; 4]>,
+}
+
+impl LineBuilder {
+    fn push(&mut self, text: &str, confidence: f64, rect: [i32; 4]) {
+        if text.trim().is_empty() {
+            return;
+        }
+        self.words.push(text.trim().to_string());
+        if confidence >= 0.0 {
+            self.confidences.push(confidence);
+        }
+        self.rect = Some(match self.rect {
+            Some([l, t, r, b]) => [
+                l.min(rect[0]),
+                t.min(rect[1]),
+                r.max(rect[2]),
+                b.max(rect[3]),
+            ],
+            None => rect,
+        });
+    }
+
+    fn finish(self, blocks: &mut Vec<TextBlock>) {
+        let Some([l, t, r, b]) = self.rect else {
+            return;
+        };
+        let score = if self.confidences.is_empty() {
+            0.0
+        } else {
+            self.confidences.iter().sum::<f64>() / self.confidences.len() as f64 / 100.0
+        };
+        blocks.push(TextBlock {
+            text: join_words(&self.words),
+            score,
+            bbox: [[l, t], [r, t], [r, b], [l, b]],
+            end: "\n".to_string(),
+        });
+    }
+}
+
+/// 拼接单词，中日韩文字之间不加空格
+fn join_words(words: &[String]) -> String {
+    let mut text = String::new();
+    for word in words {
+        let cjk_boundary =
+            text.chars().last().is_some_and(is_cjk) && word.chars().next().is_some_and(is_cjk);
+        if !text.is_empty() && !cjk_boundary {
+            text.push(' ');
+        }
+        text.push_str(word);
+    }
+    text
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3000..=0x303F | 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF
+        | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF)
+}
+
+/// 解析TSV输出，按 (block, par, line) 把单词合并为行
+fn parse_tsv(output: &str) -> Result<Vec<TextBlock>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<((u32, u32, u32, u32), LineBuilder)> = None;
+
+    for line in output.lines().skip(1) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 12 || fields[0] != "5" {
+            continue;
+        }
+        let number = |i: usize| -> Result<i64> {
+            fields[i]
+                .trim()
+                .parse::<f64>()
+                .map(|n| n as i64)
+                .map_err(|_| RsOcrError::OcrApi(format!("无法解析tesseract输出: {}", line)))
+        };
+        let key = (
+            number(1)? as u32,
+            number(2)? as u32,
+            number(3)? as u32,
+            number(4)? as u32,
+        );
+        let (left, top, width, height) = (number(6)?, number(7)?, number(8)?, number(9)?);
+        let confidence = fields[10].trim().parse::<f64>().unwrap_or(-1.0);
+        let rect = [
+            left as i32,
+            top as i32,
+            (left + width) as i32,
+            (top + height) as i32,
+        ];
+
+        match &mut current {
+            Some((current_key, builder)) if *current_key == key => {
+                builder.push(fields[11], confidence, rect)
+            }
+            _ => {
+                if let Some((_, builder)) = current.take() {
+                    builder.finish(&mut blocks);
+                }
+                let mut builder = LineBuilder::default();
+                builder.push(fields[11], confidence, rect);
+                current = Some((key, builder));
+            }
+        }
+    }
+
+    if let Some((_, builder)) = current {
+        builder.finish(&mut blocks);
+    }
+    Ok(blocks)
+}
+
+/// hOCR中表示一行文字的元素类型
+const HOCR_LINE_CLASSES: &[&str] = &["ocr_line", "ocr_caption", "ocr_header", "ocr_textfloat"];
+
+/// 解析hOCR输出，每个 `ocr_line` 元素对应一行，单词取自其中的 `ocrx_word` 元素
+fn parse_hocr(output: &str) -> Vec<TextBlock> {
+    let mut blocks = Vec::new();
+    let mut line: Option<LineBuilder> = None;
+    let mut rest = output;
+
+    while let Some(start) = rest.find("<span") {
+        rest = &rest[start..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+
+        let class = attribute(tag, "class").unwrap_or_default();
+        let title = attribute(tag, "title").unwrap_or_default();
+        if HOCR_LINE_CLASSES.contains(&class) {
+            if let Some(builder) = line.take() {
+                builder.finish(&mut blocks);
+            }
+            line = Some(LineBuilder::default());
+        } else if class == "ocrx_word" {
+            let Some(content_end) = rest.find("</span>") else {
+                break;
+            };
+            let text = unescape(&strip_tags(&rest[..content_end]));
+            rest = &rest[content_end..];
+
+            let confidence = title_property(title, "x_wconf")
+                .and_then(|v| v.first().copied())
+                .unwrap_or(-1) as f64;
+            if let Some(&[x0, y0, x1, y1]) = title_property(title, "bbox").as_deref() {
+                line.get_or_insert_with(LineBuilder::default).push(
+                    &text,
+                    confidence,
+                    [x0, y0, x1, y1],
+                );
+            }
+        }
+    }
+
+    if let Some(builder) = line {
+        builder.finish(&mut blocks);
+    }
+    blocks
+}
+
+/// 读取标签属性，支持单引号和双引号
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    for quote in ['\'', '"'] {
+        let pattern = format!("{}={}", name, quote);
+        if let Some(start) = tag.find(&pattern) {
+            let value = &tag[start + pattern.len()..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+    }
+    None
+}
+
+/// 读取 `title` 属性中的数值属性，如 `bbox 1 2 3 4; x_wconf 95`
+fn title_property(title: &str, name: &str) -> Option<Vec<i32>> {
+    title.split(';').find_map(|part| {
+        let mut items = part.split_whitespace();
+        (items.next() == Some(name)).then(|| items.filter_map(|v| v.parse().ok()).collect())
+    })
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 是否为Tesseract的语言代码，如 `chi_sim+eng` 或 `script/Latin`
+fn is_language_code(language: &str) -> bool {
+    language.split('+').all(|part| {
+        let name = part.strip_prefix("script/").unwrap_or(part);
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t200\t100\t-1\t
+4\t1\t1\t1\t1\t0\t10\t10\t120\t20\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t50\t20\t96.5\tHello
+5\t1\t1\t1\t1\t2\t70\t12\t60\t20\t91.5\tworld
+5\t1\t1\t1\t2\t1\t10\t40\t20\t20\t90\t你
+5\t1\t1\t1\t2\t2\t30\t40\t20\t20\t80\t好
+";
+
+    const HOCR: &str = r#"<div class='ocr_page' title='bbox 0 0 200 100'>
+ <span class='ocr_line' id='line_1_1' title="bbox 10 10 130 32; baseline 0 -3">
+  <span class='ocrx_word' id='word_1_1' title='bbox 10 10 60 30; x_wconf 96'>Hello</span>
+  <span class='ocrx_word' id='word_1_2' title='bbox 70 12 130 32; x_wconf 90'><strong>A&amp;B</strong></span>
+ </span>
+ <span class='ocr_line' id='line_1_2' title="bbox 10 40 50 60">
+  <span class='ocrx_word' id='word_1_3' title='bbox 10 40 50 60; x_wconf 80'>end</span>
+ </span>
+</div>"#;
+
+    #[test]
+    fn test_parse_tsv() -> Result<()> {
+        let blocks = parse_tsv(TSV)?;
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "Hello world");
+        assert_eq!(blocks[0].bbox, [[10, 10], [130, 10], [130, 32], [10, 32]]);
+        assert!((blocks[0].score - 0.94).abs() < 1e-9);
+        assert_eq!(blocks[1].text, "你好");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_hocr() {
+        let blocks = parse_hocr(HOCR);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "Hello A&B");
+        assert_eq!(blocks[0].bbox, [[10, 10], [130, 10], [130, 32], [10, 32]]);
+        assert!((blocks[0].score - 0.93).abs() < 1e-9);
+        assert_eq!(blocks[1].text, "end");
+    }
+
+    #[test]
+    fn test_language_from_options() -> Result<()> {
+        let backend = TesseractBackend::new("tesseract").languages(["chi_sim", "eng"]);
+        let language = |options: &OcrOptions| {
+            let args = backend.recognize_args("in.png", options);
+            let at = args.iter().position(|a| a == "-l").unwrap();
+            args[at + 1].clone()
+        };
+
+        // `[ocr] language` 中的Umi-OCR模型路径不是Tesseract语言代码
+        let mut config = crate::Config::default();
+        config.set("ocr.language", "models/config_chinese.txt")?;
+        assert_eq!(language(&config.ocr.to_options()), "chi_sim+eng");
+
+        let options = OcrOptions {
+            language: Some("jpn+script/Latin".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(language(&options), "jpn+script/Latin");
+        assert!(!is_language_code("eng+"));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn fake_tesseract(dir: &std::path::Path, body: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("tesseract");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fake_executable() -> Result<()> {
+        let dir = tempfile::TempDir::new().map_err(RsOcrError::Io)?;
+        let args_file = dir.path().join("args");
+        let tsv_file = dir.path().join("out.tsv");
+        std::fs::write(&tsv_file, TSV).map_err(RsOcrError::Io)?;
+        let script = fake_tesseract(
+            dir.path(),
+            &format!(
+                "echo \"$@\" > '{}'\ncat > /dev/null\ncat '{}'",
+                args_file.display(),
+                tsv_file.display()
+            ),
+        );
+
+        let backend = TesseractBackend::new(script)
+            .languages(["chi_sim", "eng"])
+            .psm(6);
+        let result = backend.recognize_bytes(b"image", &OcrOptions::default())?;
+        assert_eq!(result.text(), "Hello world\n你好");
+        let args = std::fs::read_to_string(&args_file).map_err(RsOcrError::Io)?;
+        assert_eq!(args.trim(), "stdin stdout -l chi_sim+eng --psm 6 tsv");
+
+        // 请求选项中的语言优先
+        let options = OcrOptions {
+            language: Some("jpn".to_string()),
+            ..Default::default()
+        };
+        backend.recognize_bytes(b"image", &options)?;
+        let args = std::fs::read_to_string(&args_file).map_err(RsOcrError::Io)?;
+        assert!(args.contains("-l jpn "));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failure_and_timeout() -> Result<()> {
+        let dir = tempfile::TempDir::new().map_err(RsOcrError::Io)?;
+        let failing = fake_tesseract(dir.path(), "echo 'Error opening data file' >&2\nexit 1");
+        let err = TesseractBackend::new(failing)
+            .recognize_bytes(b"image", &OcrOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("Error opening data file"));
+
+        let slow_dir = tempfile::TempDir::new().map_err(RsOcrError::Io)?;
+        let slow = fake_tesseract(slow_dir.path(), "sleep 5");
+        let started = Instant::now();
+        let err = TesseractBackend::new(slow)
+            .timeout(Duration::from_millis(200))
+            .recognize_bytes(b"image", &OcrOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("超时"));
+        assert!(started.elapsed() < Duration::from_secs(3));
+
+        let err = TesseractBackend::new("/non/existent/tesseract")
+            .health()
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrApi(_)));
+        Ok(())
+    }
+}
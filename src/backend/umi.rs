@@ -0,0 +1,65 @@
+//! Umi-OCR HTTP后端，由 [`OcrClient`] 实现
+
+use super::OcrBackend;
+use crate::capabilities::ServerCapabilities;
+use crate::client::OcrClient;
+use crate::models::{OcrOptions, OcrResult, Result};
+use base64::{Engine as _, engine::general_purpose};
+
+impl OcrBackend for OcrClient {
+    fn name(&self) -> &'static str {
+        "umi"
+    }
+
+    fn endpoint(&self) -> &str {
+        self.url()
+    }
+
+    fn recognize_bytes(&self, image: &[u8], options: &OcrOptions) -> Result<OcrResult> {
+        self.recognize_with_options(&general_purpose::STANDARD.encode(image), options)
+    }
+
+    fn recognize_path(&self, image_path: &str, options: &OcrOptions) -> Result<OcrResult> {
+        self.recognize_file_with_options(image_path, options)
+    }
+
+    fn health(&self) -> Result<()> {
+        OcrClient::health(self)
+    }
+
+    fn validate_options(&self, options: &OcrOptions) -> Result<()> {
+        OcrClient::validate_options(self, options)
+    }
+
+    fn capabilities(&self) -> Result<ServerCapabilities> {
+        OcrClient::capabilities(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[test]
+    fn test_umi_backend() -> Result<()> {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::text(200, "ok"),
+            _ => MockResponse::umi_text("你好", 0.9),
+        });
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let backend: &dyn OcrBackend = &client;
+
+        backend.health()?;
+        let options = OcrOptions {
+            cls: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(backend.recognize_bytes(b"img", &options)?.text(), "你好");
+
+        let request = &server.requests()[1];
+        assert_eq!(request.json()["base64"], "aW1n");
+        assert_eq!(request.json()["options"]["ocr.cls"], true);
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+//! OCR服务能力查询
+//!
+//! 通过 Umi-OCR 的 `/api/ocr/get_options` 接口获取服务器支持的识别语言、
+//! 排版解析方案和默认参数，用于校验用户选择的 [`OcrOptions`] 并填充GUI下拉框。
+
+use crate::client::OcrClient;
+use crate::models::{OcrOptions, Result, RsOcrError, TbpuParser};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// 服务器提供的一个可选值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionChoice {
+    /// 发送给服务器的值
+    pub value: String,
+    /// 显示名称
+    pub label: String,
+}
+
+/// 服务器支持的OCR参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerCapabilities {
+    /// 可用的识别语言（模型配置）
+    pub languages: Vec<OptionChoice>,
+    /// 可用的最长边限制
+    pub limit_side_lens: Vec<OptionChoice>,
+    /// 可用的排版解析方案
+    pub parsers: Vec<OptionChoice>,
+    /// 服务器的默认参数
+    pub defaults: OcrOptions,
+}
+
+impl ServerCapabilities {
+    /// 从 `get_options` 接口的响应解析服务器能力
+    pub fn from_json(json: &Value) -> Self {
+        let language = json.get("ocr.language");
+        let limit_side_len = json.get("ocr.limit_side_len");
+        let parser = json.get("tbpu.parser");
+
+        let defaults = OcrOptions {
+            language: default_of(language).map(value_to_string),
+            cls: default_of(json.get("ocr.cls")).and_then(Value::as_bool),
+            limit_side_len: default_of(limit_side_len)
+                .and_then(Value::as_u64)
+                .and_then(|n| u32::try_from(n).ok()),
+            parser: default_of(parser)
+                .and_then(Value::as_str)
+                .and_then(TbpuParser::from_name),
+            ..Default::default()
+        };
+
+        Self {
+            languages: choices_of(language),
+            limit_side_lens: choices_of(limit_side_len),
+            parsers: choices_of(parser),
+            defaults,
+        }
+    }
+
+    /// 服务器支持且本库已知的排版解析方案
+    pub fn supported_parsers(&self) -> Vec<TbpuParser> {
+        self.parsers
+            .iter()
+            .filter_map(|choice| TbpuParser::from_name(&choice.value))
+            .collect()
+    }
+
+    /// 校验OCR请求选项是否被服务器支持
+    ///
+    /// 服务器未提供某项参数的可选值列表时，不校验该项。
+    ///
+    /// # 返回
+    /// * `Result<()>` - 存在不支持的参数时返回 [`RsOcrError::Config`]
+    pub fn validate(&self, options: &OcrOptions) -> Result<()> {
+        if let Some(language) = &options.language {
+            check_choice(&self.languages, language, "识别语言")?;
+        }
+        if let Some(limit_side_len) = options.limit_side_len {
+            check_choice(
+                &self.limit_side_lens,
+                &limit_side_len.to_string(),
+                "最长边限制",
+            )?;
+        }
+        if let Some(parser) = options.parser {
+            check_choice(&self.parsers, parser.as_str(), "排版解析方案")?;
+        }
+        Ok(())
+    }
+}
+
+fn default_of(option: Option<&Value>) -> Option<&Value> {
+    option.and_then(|o| o.get("default"))
+}
+
+fn choices_of(option: Option<&Value>) -> Vec<OptionChoice> {
+    let list = option.and_then(|o| o.get("optionsList").or_else(|| o.get("options")));
+
+    list.and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let pair = item.as_array()?;
+                    let value = value_to_string(pair.first()?);
+                    let label = pair
+                        .get(1)
+                        .map(value_to_string)
+                        .unwrap_or_else(|| value.clone());
+                    Some(OptionChoice { value, label })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn check_choice(choices: &[OptionChoice], value: &str, name: &str) -> Result<()> {
+    if choices.is_empty() || choices.iter().any(|c| c.value == value) {
+        return Ok(());
+    }
+
+    let available = choices
+        .iter()
+        .map(|c| c.value.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(RsOcrError::Config(format!(
+        "服务器不支持的{}: {}（可选值: {}）",
+        name, value, available
+    )))
+}
+
+/// 根据OCR API地址得到参数查询接口的地址
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::capabilities::options_url;
+///
+/// assert_eq!(
+///     options_url("http://127.0.0.1:1224/api/ocr"),
+///     "http://127.0.0.1:1224/api/ocr/get_options"
+/// );
+/// ```
+pub fn options_url(api_url: &str) -> String {
+    format!("{}/get_options", api_url.trim_end_matches('/'))
+}
+
+/// 向服务器查询支持的OCR参数（不使用缓存）
+///
+/// # 参数
+/// * `api_url` - OCR API的URL
+///
+/// # 返回
+/// * `Result<ServerCapabilities>` - 成功时返回服务器能力，失败时返回错误
+pub fn fetch_capabilities(api_url: &str) -> Result<ServerCapabilities> {
+    OcrClient::shared(api_url)?.fetch_capabilities()
+}
+
+pub(crate) fn check_status(status: reqwest::StatusCode) -> Result<()> {
+    if status.is_success() {
+        return Ok(());
+    }
+    Err(RsOcrError::OcrApi(format!(
+        "查询服务器参数失败: HTTP状态码 {} ({})",
+        status,
+        status.canonical_reason().unwrap_or("未知错误")
+    )))
+}
+
+fn cache() -> &'static Mutex<HashMap<String, ServerCapabilities>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, ServerCapabilities>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(crate) fn cached(api_url: &str) -> Option<ServerCapabilities> {
+    cache().lock().ok().and_then(|c| c.get(api_url).cloned())
+}
+
+pub(crate) fn store(api_url: &str, capabilities: &ServerCapabilities) {
+    if let Ok(mut cache) = cache().lock() {
+        cache.insert(api_url.to_string(), capabilities.clone());
+    }
+}
+
+/// 查询服务器支持的OCR参数，结果按API地址缓存
+///
+/// 只缓存查询成功的结果，查询失败时下次调用会重新请求。
+///
+/// # 参数
+/// * `api_url` - OCR API的URL
+///
+/// # 返回
+/// * `Result<ServerCapabilities>` - 成功时返回服务器能力，失败时返回错误
+pub fn get_capabilities(api_url: &str) -> Result<ServerCapabilities> {
+    OcrClient::shared(api_url)?.capabilities()
+}
+
+/// 优先使用缓存，否则调用 `fetch` 查询并缓存成功的结果
+pub(crate) fn get_or_fetch(
+    api_url: &str,
+    fetch: impl FnOnce() -> Result<ServerCapabilities>,
+) -> Result<ServerCapabilities> {
+    if let Some(cached) = cached(api_url) {
+        return Ok(cached);
+    }
+
+    let capabilities = fetch()?;
+    store(api_url, &capabilities);
+    Ok(capabilities)
+}
+
+/// 清除指定API地址的缓存
+pub fn invalidate_capabilities(api_url: &str) {
+    if let Ok(mut cache) = cache().lock() {
+        cache.remove(api_url);
+    }
+}
+
+/// 发送请求前校验OCR请求选项
+///
+/// 只有设置了语言、最长边或排版解析方案时才查询服务器。
+/// 服务器不提供参数查询接口时记录警告并跳过校验。
+///
+/// # 参数
+/// * `api_url` - OCR API的URL
+/// * `options` - 要校验的OCR请求选项
+///
+/// # 返回
+/// * `Result<()>` - 存在服务器不支持的参数时返回 [`RsOcrError::Config`]
+pub fn validate_options(api_url: &str, options: &OcrOptions) -> Result<()> {
+    OcrClient::shared(api_url)?.validate_options(options)
+}
+
+/// 是否设置了需要向服务器确认的参数
+pub(crate) fn needs_validation(options: &OcrOptions) -> bool {
+    options.language.is_some() || options.limit_side_len.is_some() || options.parser.is_some()
+}
+
+/// 用查询结果校验选项，查询失败时跳过校验
+pub(crate) fn validate_against(
+    capabilities: Result<ServerCapabilities>,
+    options: &OcrOptions,
+) -> Result<()> {
+    match capabilities {
+        Ok(capabilities) => capabilities.validate(options),
+        Err(e) => {
+            log::warn!("无法查询服务器参数，跳过校验: {}", e);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    fn sample_options() -> Value {
+        serde_json::json!({
+            "ocr.language": {
+                "title": "语言/模型库",
+                "optionsList": [
+                    ["models/config_chinese.txt", "简体中文"],
+                    ["models/config_en.txt", "English"]
+                ],
+                "type": "enum",
+                "default": "models/config_chinese.txt"
+            },
+            "ocr.cls": {"title": "纠正文本方向", "type": "boolean", "default": false},
+            "ocr.limit_side_len": {
+                "optionsList": [[960, "960"], [2880, "2880"]],
+                "type": "enum",
+                "default": 960
+            },
+            "tbpu.parser": {
+                "optionsList": [
+                    ["multi_para", "多栏-按自然段换行"],
+                    ["single_code", "单栏-保留缩进"],
+                    ["future_parser", "未知方案"]
+                ],
+                "type": "enum",
+                "default": "multi_para"
+            }
+        })
+    }
+
+    #[test]
+    fn test_from_json() {
+        let capabilities = ServerCapabilities::from_json(&sample_options());
+
+        assert_eq!(capabilities.languages.len(), 2);
+        assert_eq!(capabilities.languages[1].label, "English");
+        assert_eq!(capabilities.limit_side_lens[0].value, "960");
+        assert_eq!(
+            capabilities.supported_parsers(),
+            vec![TbpuParser::MultiPara, TbpuParser::SingleCode]
+        );
+        assert_eq!(
+            capabilities.defaults.language,
+            Some("models/config_chinese.txt".to_string())
+        );
+        assert_eq!(capabilities.defaults.cls, Some(false));
+        assert_eq!(capabilities.defaults.limit_side_len, Some(960));
+        assert_eq!(capabilities.defaults.parser, Some(TbpuParser::MultiPara));
+    }
+
+    #[test]
+    fn test_validate() {
+        let capabilities = ServerCapabilities::from_json(&sample_options());
+
+        let valid = OcrOptions {
+            language: Some("models/config_en.txt".to_string()),
+            limit_side_len: Some(2880),
+            parser: Some(TbpuParser::SingleCode),
+            ..Default::default()
+        };
+        assert!(capabilities.validate(&valid).is_ok());
+
+        let invalid = OcrOptions {
+            language: Some("models/config_klingon.txt".to_string()),
+            ..Default::default()
+        };
+        let err = capabilities.validate(&invalid).unwrap_err();
+        assert!(err.to_string().contains("models/config_klingon.txt"));
+
+        let invalid = OcrOptions {
+            parser: Some(TbpuParser::VerticalRl),
+            ..Default::default()
+        };
+        assert!(capabilities.validate(&invalid).is_err());
+
+        // 服务器未提供列表时不校验
+        assert!(ServerCapabilities::default().validate(&invalid).is_ok());
+    }
+
+    #[test]
+    fn test_get_capabilities_is_cached() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::json(sample_options()));
+        let api_url = format!("{}/api/ocr", server.url());
+
+        let first = get_capabilities(&api_url)?;
+        let second = get_capabilities(&api_url)?;
+        assert_eq!(first, second);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/api/ocr/get_options");
+
+        invalidate_capabilities(&api_url);
+        get_capabilities(&api_url)?;
+        assert_eq!(server.requests().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_options_without_endpoint() {
+        let server = MockServer::start(|_| MockResponse::text(404, "not found"));
+        let api_url = format!("{}/api/ocr", server.url());
+
+        let options = OcrOptions {
+            language: Some("anything".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_options(&api_url, &options).is_ok());
+    }
+}
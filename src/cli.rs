@@ -1,30 +1,277 @@
-use clap::Parser;
+use crate::backend::{OcrBackend, create_backend};
+use crate::client::shared_http_client;
+use crate::config::{Config, PreprocessConfig};
+use crate::image_format;
+use crate::input::ImageSource;
+use crate::models::{
+    IgnoreArea, OcrOptions, PageResult, Result, RsOcrError, TbpuParser, parse_ignore_area,
+};
+use crate::ocr_utils::collect_images;
+use crate::pages::PageRange;
+use crate::preprocess::{OutputFormat, PreprocessStep};
+use clap::{Parser, Subcommand};
+use reqwest::blocking::Client;
+use std::path::Path;
+
+/// 命令行模式退出码：识别成功
+pub const EXIT_SUCCESS: i32 = 0;
+/// 命令行模式退出码：OCR服务返回错误或请求失败
+pub const EXIT_OCR_FAILED: i32 = 1;
+/// 命令行模式退出码：参数或配置错误
+pub const EXIT_USAGE: i32 = 2;
+/// 命令行模式退出码：读取图片失败
+pub const EXIT_IO: i32 = 3;
 
 /// 命令行参数结构体
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
-    /// 图片文件路径
+    /// 子命令，不提供时按旧版参数运行
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// 图片文件路径、URL或data URI，`-` 表示标准输入（旧版参数，建议使用 `ocr` 子命令）
     #[arg(short, long)]
     pub file: Option<String>,
 
     /// OCR API URL
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub url: Option<String>,
 
+    /// OCR后端名称，如 umi
+    #[arg(long, global = true)]
+    pub backend: Option<String>,
+
     /// 保存当前配置到配置文件
     #[arg(short, long)]
     pub save: bool,
+
+    /// 多页TIFF和GIF动画要识别的页码，如 1-3,5,8-，默认识别TIFF的全部页面和GIF的第1帧
+    #[arg(long, global = true, value_parser = parse_pages_arg)]
+    pub pages: Option<PageRange>,
+
+    /// OCR识别参数
+    #[command(flatten)]
+    pub ocr: OcrArgs,
+
+    /// 图片预处理参数
+    #[command(flatten)]
+    pub preprocess: PreprocessArgs,
+}
+
+/// OCR识别参数，未提供的参数使用配置文件中的值
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct OcrArgs {
+    /// 识别语言（模型配置），如 models/config_chinese.txt
+    #[arg(long, global = true)]
+    pub language: Option<String>,
+
+    /// 是否启用文字方向纠正
+    #[arg(long, global = true)]
+    pub cls: Option<bool>,
+
+    /// 图片最长边的限制
+    #[arg(long, global = true)]
+    pub limit_side_len: Option<u32>,
+
+    /// 排版解析方案
+    #[arg(long, global = true, value_enum)]
+    pub parser: Option<TbpuParser>,
+
+    /// 忽略区域，格式为 x0,y0,x1,y1，可重复指定
+    #[arg(long, global = true, value_parser = parse_ignore_area_arg)]
+    pub ignore_area: Vec<IgnoreArea>,
+}
+
+impl OcrArgs {
+    /// 用命令行参数覆盖OCR请求选项
+    ///
+    /// # 参数
+    /// * `options` - 来自配置文件的OCR请求选项
+    ///
+    /// # 返回
+    /// * `OcrOptions` - 合并后的OCR请求选项
+    pub fn merge(&self, mut options: OcrOptions) -> OcrOptions {
+        if let Some(language) = &self.language {
+            options.language = Some(language.clone());
+        }
+        if let Some(cls) = self.cls {
+            options.cls = Some(cls);
+        }
+        if let Some(limit_side_len) = self.limit_side_len {
+            options.limit_side_len = Some(limit_side_len);
+        }
+        if let Some(parser) = self.parser {
+            options.parser = Some(parser);
+        }
+        if !self.ignore_area.is_empty() {
+            options.ignore_area = Some(self.ignore_area.clone());
+        }
+        options
+    }
+}
+
+/// 图片预处理参数，未提供的参数使用配置文件中的值
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct PreprocessArgs {
+    /// 上传前按顺序执行的预处理步骤，用逗号分隔，如 grayscale,contrast,upscale
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    pub preprocess: Vec<PreprocessStep>,
+
+    /// 不执行预处理，忽略配置文件中的步骤
+    #[arg(long, global = true, conflicts_with = "preprocess")]
+    pub no_preprocess: bool,
+
+    /// upscale 步骤的放大倍数
+    #[arg(long, global = true)]
+    pub upscale_factor: Option<f32>,
+
+    /// 上传图片最长边的上限（像素），超过时等比缩小
+    #[arg(long, global = true)]
+    pub max_side: Option<u32>,
+
+    /// 上传图片的字节数上限，超过时降低JPEG质量或缩小图片
+    #[arg(long, global = true)]
+    pub max_bytes: Option<u64>,
+
+    /// 重新编码上传图片的格式
+    #[arg(long, global = true, value_enum)]
+    pub upload_format: Option<OutputFormat>,
+
+    /// JPEG质量（1~100）
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub jpeg_quality: Option<u8>,
+
+    /// 自动检测方向：分别旋转0°、90°、180°、270°识别，保留平均置信度最高的结果
+    #[arg(long, global = true)]
+    pub auto_rotate: bool,
+}
+
+impl PreprocessArgs {
+    /// 用命令行参数覆盖预处理设置
+    ///
+    /// # 参数
+    /// * `config` - 来自配置文件的预处理设置
+    pub fn apply(&self, config: &mut PreprocessConfig) {
+        if self.no_preprocess {
+            config.steps = None;
+        } else if !self.preprocess.is_empty() {
+            config.steps = Some(self.preprocess.clone());
+        }
+        if let Some(factor) = self.upscale_factor {
+            config.upscale_factor = Some(factor);
+        }
+        if let Some(max_side) = self.max_side {
+            config.max_side = Some(max_side);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            config.max_bytes = Some(max_bytes);
+        }
+        if let Some(format) = self.upload_format {
+            config.format = Some(format);
+        }
+        if let Some(quality) = self.jpeg_quality {
+            config.jpeg_quality = Some(quality);
+        }
+        if self.auto_rotate {
+            config.auto_rotate = Some(true);
+        }
+    }
+}
+
+fn parse_ignore_area_arg(value: &str) -> std::result::Result<IgnoreArea, String> {
+    parse_ignore_area(value).map_err(|e| e.to_string())
+}
+
+fn parse_pages_arg(value: &str) -> std::result::Result<PageRange, String> {
+    value.parse().map_err(|e: RsOcrError| e.to_string())
+}
+
+/// 子命令
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// 识别一个或多个图片
+    Ocr {
+        /// 图片文件路径、http(s) URL或 `data:image/...;base64,` URI，`-` 表示标准输入
+        #[arg(required = true)]
+        files: Vec<String>,
+    },
+
+    /// 批量识别目录中的图片
+    Batch {
+        /// 图片所在目录
+        dir: String,
+
+        /// 递归处理子目录
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// 将结果写入与图片同名的 `.txt` 文件，而不是输出到标准输出
+        #[arg(short, long)]
+        write: bool,
+    },
+
+    /// 查看或修改配置文件
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// 检查OCR服务是否可用
+    Health,
+
+    /// 启动图形界面
+    Gui,
+}
+
+/// `config` 子命令的操作
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ConfigCommand {
+    /// 读取配置项
+    Get {
+        /// 配置项名称，嵌套项使用 `.` 分隔
+        key: String,
+        /// 显示明文密钥，默认显示为 `***`
+        #[arg(long)]
+        show_secrets: bool,
+    },
+
+    /// 修改配置项并保存
+    Set {
+        /// 配置项名称，嵌套项使用 `.` 分隔
+        key: String,
+        /// 新的值
+        value: String,
+    },
+
+    /// 列出全部配置
+    List {
+        /// 显示明文密钥，默认显示为 `***`
+        #[arg(long)]
+        show_secrets: bool,
+    },
+
+    /// 输出配置文件路径
+    Path,
 }
 
 impl Args {
     /// 解析命令行参数
     ///
     /// 这个函数解析命令行参数并返回 `Args` 结构体实例。
-    /// 它使用 `clap` 库进行参数解析，支持以下参数：
-    /// - `-f, --file <图片路径>`: 指定要处理的图片文件路径
+    /// 它使用 `clap` 库进行参数解析，支持以下子命令：
+    /// - `ocr <图片>...`: 识别一个或多个图片，`-` 表示标准输入
+    /// - `batch <目录>`: 批量识别目录中的图片
+    /// - `config get|set|list|path`: 查看或修改配置文件
+    /// - `health`: 检查OCR服务是否可用
+    /// - `gui`: 启动图形界面
+    ///
+    /// 以及以下参数：
+    /// - `-f, --file <图片路径>`: 指定要处理的图片文件路径（旧版参数）
     /// - `-u, --url <API地址>`: 指定OCR API的URL（可选，默认为 `http://127.0.0.1:1224/api/ocr`）
+    /// - `--backend <名称>`: 指定OCR后端（可选，默认为配置文件中的 `backend` 或 `umi`）
     /// - `-s, --save`: 保存当前配置到配置文件
+    /// - `--pages <页码>`: 多页TIFF和GIF动画要识别的页码（可选，默认为TIFF的全部页面和GIF的第1帧）
     ///
     /// # 返回
     /// * `Self` - 解析后的命令行参数结构体
@@ -42,6 +289,347 @@ impl Args {
     pub fn parse_args() -> Self {
         Self::parse()
     }
+
+    /// 是否以命令行模式运行
+    ///
+    /// 提供了除 `gui` 以外的子命令或任何OCR相关参数时以命令行模式运行，否则启动GUI。
+    pub fn is_headless(&self) -> bool {
+        match &self.command {
+            Some(Command::Gui) => false,
+            Some(_) => true,
+            None => self.has_ocr_args(),
+        }
+    }
+
+    /// 检查启动GUI时是否提供了OCR相关参数
+    ///
+    /// 图形界面只读取配置文件，`gui` 子命令的这些参数不会生效。
+    ///
+    /// # 返回
+    /// * `Result<()>` - 提供了OCR相关参数时返回 [`RsOcrError::Config`]
+    pub fn check_gui(&self) -> Result<()> {
+        if self.has_ocr_args() {
+            return Err(RsOcrError::Config(
+                "gui 子命令不支持识别参数，请在图形界面中设置，或使用 config set 保存到配置文件"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 是否提供了任何OCR相关参数
+    fn has_ocr_args(&self) -> bool {
+        self.file.is_some()
+            || self.url.is_some()
+            || self.backend.is_some()
+            || self.save
+            || self.pages.is_some()
+            || self.ocr != OcrArgs::default()
+            || self.preprocess != PreprocessArgs::default()
+    }
+}
+
+/// 以命令行模式运行
+///
+/// 读取配置文件并执行子命令。识别结果输出到标准输出，错误信息输出到标准错误。
+/// 未提供子命令时按旧版参数运行：仅提供 `--save` 而未提供 `--file` 时只保存配置。
+///
+/// # 参数
+/// * `args` - 解析后的命令行参数
+///
+/// # 返回
+/// * `i32` - 进程退出码，见 [`EXIT_SUCCESS`]、[`EXIT_OCR_FAILED`]、[`EXIT_USAGE`]、[`EXIT_IO`]
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::{Args, cli};
+///
+/// let args = Args::parse_args();
+/// std::process::exit(cli::run(args));
+/// ```
+pub fn run(args: Args) -> i32 {
+    run_with_config(args, Config::load())
+}
+
+/// 使用给定的配置以命令行模式运行，见 [`run`]
+///
+/// # 参数
+/// * `args` - 解析后的命令行参数
+/// * `config` - 配置，`config set` 等子命令会把修改保存到配置文件
+///
+/// # 返回
+/// * `i32` - 进程退出码
+pub fn run_with_config(args: Args, mut config: Config) -> i32 {
+    if let Some(backend) = args.backend {
+        config.backend = Some(backend);
+    }
+    args.preprocess.apply(&mut config.preprocess);
+    let options = args.ocr.merge(config.ocr.to_options());
+    let pages = args.pages.unwrap_or_default();
+    let backend = |url: Option<String>| create_backend(&config, url);
+
+    let result = match args.command {
+        None => run_legacy(&config, args.file, args.url, args.save, &options, &pages),
+        Some(Command::Ocr { files }) => {
+            backend(args.url).and_then(|b| run_ocr(b.as_ref(), &config, &options, &pages, &files))
+        }
+        Some(Command::Batch {
+            dir,
+            recursive,
+            write,
+        }) => backend(args.url)
+            .and_then(|b| run_batch(b.as_ref(), &options, &pages, &dir, recursive, write)),
+        Some(Command::Health) => backend(args.url).and_then(|b| run_health(b.as_ref())),
+        Some(Command::Config { action }) => run_config(&mut config, action),
+        Some(Command::Gui) => Err(RsOcrError::Config(
+            "gui 子命令不能在命令行模式下运行".to_string(),
+        )),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("错误: {}", e);
+            exit_code(&e)
+        }
+    }
+}
+
+/// 根据错误类型获取进程退出码
+pub fn exit_code(err: &RsOcrError) -> i32 {
+    match err {
+        RsOcrError::Config(_) => EXIT_USAGE,
+        RsOcrError::Io(_) | RsOcrError::Base64(_) | RsOcrError::ImageProcessing(_) => EXIT_IO,
+        _ => EXIT_OCR_FAILED,
+    }
+}
+
+/// 识别图片的指定页，没有识别到文字时在标准错误中提示
+///
+/// 本地文件交给后端读取，其他来源先读入内存，URL使用 `http` 下载。
+fn recognize(
+    backend: &dyn OcrBackend,
+    http: &Client,
+    options: &OcrOptions,
+    pages: &PageRange,
+    source: &ImageSource,
+) -> Result<Vec<PageResult>> {
+    let results = match source {
+        ImageSource::Path(path) => {
+            backend.recognize_path_pages(&path.to_string_lossy(), options, pages)?
+        }
+        other => backend.recognize_pages(&other.read_with(http)?, options, pages)?,
+    };
+    for page in &results {
+        let name = match results.len() {
+            1 => source.to_string(),
+            _ => format!("{} 第 {} 页", source, page.page),
+        };
+        if page.result.is_empty() {
+            eprintln!("未识别到文字: {}", name);
+        }
+        if let Some(rotation @ 1..) = page.result.rotation {
+            eprintln!("已旋转 {}° 后识别: {}", rotation, name);
+        }
+    }
+    Ok(results)
+}
+
+/// 下载URL图片的客户端，只有来源中有URL时才按 `[client]` 构建
+fn download_client(config: &Config, sources: &[ImageSource]) -> Result<Client> {
+    if sources.iter().any(|s| matches!(s, ImageSource::Url(_))) {
+        config.download_client()
+    } else {
+        Ok(shared_http_client().clone())
+    }
+}
+
+/// 拼接各页的文本
+///
+/// 只识别了第1页时直接返回它的文本，否则在每页前加上 `--- 第 N 页 ---` 分隔行。
+fn pages_text(results: &[PageResult]) -> String {
+    if let [only] = results
+        && only.page == 1
+    {
+        return only.result.text();
+    }
+    results
+        .iter()
+        .map(|page| format!("--- 第 {} 页 ---\n{}", page.page, page.result.text()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn run_legacy(
+    config: &Config,
+    file: Option<String>,
+    url: Option<String>,
+    save: bool,
+    options: &OcrOptions,
+    pages: &PageRange,
+) -> Result<i32> {
+    let mut config = config.clone();
+    if save {
+        config.update_with_args(file.clone(), url.clone());
+        config.ocr.update_with_options(options);
+        config.save()?;
+        eprintln!("配置已保存到: {}", Config::config_path().display());
+
+        if file.is_none() {
+            return Ok(EXIT_SUCCESS);
+        }
+    }
+
+    let (file, url) = config.merge_with_args(file, url)?;
+    let backend = create_backend(&config, Some(url))?;
+    backend.validate_options(options)?;
+    log::info!("开始处理图片: {}", file);
+
+    let source = ImageSource::parse(&file);
+    let http = download_client(&config, std::slice::from_ref(&source))?;
+    let results = recognize(backend.as_ref(), &http, options, pages, &source)?;
+    println!("{}", pages_text(&results));
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_ocr(
+    backend: &dyn OcrBackend,
+    config: &Config,
+    options: &OcrOptions,
+    pages: &PageRange,
+    files: &[String],
+) -> Result<i32> {
+    backend.validate_options(options)?;
+
+    let sources: Vec<ImageSource> = files.iter().map(|f| ImageSource::parse(f)).collect();
+    if sources.iter().filter(|s| **s == ImageSource::Stdin).count() > 1 {
+        return Err(RsOcrError::Config("标准输入 `-` 只能指定一次".to_string()));
+    }
+    let http = download_client(config, &sources)?;
+
+    // 只有一个文件时错误直接返回，以便得到对应的退出码
+    if let [source] = sources.as_slice() {
+        println!(
+            "{}",
+            pages_text(&recognize(backend, &http, options, pages, source)?)
+        );
+        return Ok(EXIT_SUCCESS);
+    }
+
+    let mut code = EXIT_SUCCESS;
+    for source in &sources {
+        match recognize(backend, &http, options, pages, source) {
+            Ok(results) => {
+                println!("==> {} <==", source);
+                println!("{}", pages_text(&results));
+            }
+            Err(e) => {
+                eprintln!("错误: {}: {}", source, e);
+                code = exit_code(&e);
+            }
+        }
+    }
+
+    Ok(code)
+}
+
+fn run_batch(
+    backend: &dyn OcrBackend,
+    options: &OcrOptions,
+    pages: &PageRange,
+    dir: &str,
+    recursive: bool,
+    write: bool,
+) -> Result<i32> {
+    backend.validate_options(options)?;
+    let images = collect_images(Path::new(dir), recursive)?;
+    if images.is_empty() {
+        eprintln!("目录中没有图片: {}", dir);
+        return Ok(EXIT_SUCCESS);
+    }
+
+    let mut failed = 0;
+    let mut skipped = 0;
+    for image in &images {
+        let path = image.to_string_lossy();
+        // 单页图片不在页码范围内时跳过，而不是算作失败
+        if !pages.contains(1)
+            && matches!(image_format::sniff_file(image), Ok(Some(kind)) if !kind.can_have_pages())
+        {
+            eprintln!("跳过: {}: 只有1页，不在页码范围 {} 内", path, pages);
+            skipped += 1;
+            continue;
+        }
+        let source = ImageSource::Path(image.clone());
+        let result =
+            recognize(backend, shared_http_client(), options, pages, &source).and_then(|results| {
+                let text = pages_text(&results);
+                if write {
+                    let output = image.with_extension("txt");
+                    std::fs::write(&output, &text).map_err(RsOcrError::Io)?;
+                    eprintln!("{} -> {}", path, output.display());
+                } else {
+                    println!("==> {} <==", path);
+                    println!("{}", text);
+                }
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            eprintln!("错误: {}: {}", path, e);
+            failed += 1;
+        }
+    }
+
+    eprintln!(
+        "处理完成: 成功 {}，失败 {}，跳过 {}",
+        images.len() - failed - skipped,
+        failed,
+        skipped
+    );
+    Ok(if failed == 0 {
+        EXIT_SUCCESS
+    } else {
+        EXIT_OCR_FAILED
+    })
+}
+
+fn run_config(config: &mut Config, action: ConfigCommand) -> Result<i32> {
+    match action {
+        ConfigCommand::Get { key, show_secrets } => {
+            let shown = if show_secrets {
+                config.get(&key)?
+            } else {
+                config.redacted().get(&key)?
+            };
+            match shown {
+                Some(value) => println!("{}", value),
+                None => eprintln!("配置项未设置: {}", key),
+            }
+        }
+        ConfigCommand::Set { key, value } => {
+            config.set(&key, &value)?;
+            config.save()?;
+        }
+        ConfigCommand::List { show_secrets } => {
+            let shown = if show_secrets {
+                config.to_toml()?
+            } else {
+                config.redacted().to_toml()?
+            };
+            print!("{}", shown);
+        }
+        ConfigCommand::Path => println!("{}", Config::config_path().display()),
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+fn run_health(backend: &dyn OcrBackend) -> Result<i32> {
+    backend.health()?;
+    println!("OCR服务可用: [{}] {}", backend.name(), backend.endpoint());
+    Ok(EXIT_SUCCESS)
 }
 
 #[cfg(test)]
@@ -53,9 +641,14 @@ mod tests {
     fn test_args_struct() {
         // 测试结构体可以正常实例化
         let args = Args {
+            command: None,
             file: Some("test.png".to_string()),
             url: Some("http://test.com/api".to_string()),
+            backend: None,
             save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
         };
 
         assert_eq!(args.file, Some("test.png".to_string()));
@@ -93,9 +686,14 @@ mod tests {
     fn test_field_documentation() {
         // 验证字段有文档注释（通过编译检查）
         let args = Args {
+            command: None,
             file: None,
             url: None,
+            backend: None,
             save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
         };
 
         // 如果结构体字段有文档，这些字段应该可访问
@@ -105,4 +703,356 @@ mod tests {
 
         // 验证代码可以编译和执行（没有panic）
     }
+
+    #[test]
+    fn test_is_headless() {
+        let args = Args {
+            command: None,
+            file: None,
+            url: None,
+            backend: None,
+            save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
+        };
+        assert!(!args.is_headless());
+
+        let args = Args {
+            command: None,
+            file: Some("image.png".to_string()),
+            url: None,
+            backend: None,
+            save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
+        };
+        assert!(args.is_headless());
+
+        let args = Args {
+            command: None,
+            file: None,
+            url: Some("http://api.test.com".to_string()),
+            backend: None,
+            save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
+        };
+        assert!(args.is_headless());
+
+        // 全局的识别和预处理参数也选择命令行模式
+        for flags in [
+            &["--cls", "true"][..],
+            &["--language", "models/config_chinese.txt"],
+            &["--preprocess", "grayscale"],
+            &["--pages", "2"],
+        ] {
+            let args = Args::try_parse_from(std::iter::once("rsocr").chain(flags.iter().copied()))
+                .unwrap();
+            assert!(args.is_headless(), "{:?}", flags);
+        }
+
+        // gui 子命令拒绝不会生效的参数
+        let args = Args::try_parse_from(["rsocr", "gui"]).unwrap();
+        assert!(!args.is_headless());
+        assert!(args.check_gui().is_ok());
+        let args =
+            Args::try_parse_from(["rsocr", "gui", "--url", "http://x", "--cls", "true"]).unwrap();
+        assert!(!args.is_headless());
+        assert!(matches!(args.check_gui(), Err(RsOcrError::Config(_))));
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let args = Args::try_parse_from(["rsocr", "ocr", "a.png", "b.png", "-u", "http://x"])
+            .expect("ocr 子命令应能解析");
+        assert_eq!(
+            args.command,
+            Some(Command::Ocr {
+                files: vec!["a.png".to_string(), "b.png".to_string()]
+            })
+        );
+        assert_eq!(args.url, Some("http://x".to_string()));
+        assert!(args.is_headless());
+
+        let args = Args::try_parse_from(["rsocr", "batch", "images", "-r"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Batch {
+                dir: "images".to_string(),
+                recursive: true,
+                write: false,
+            })
+        );
+
+        let args = Args::try_parse_from(["rsocr", "config", "set", "url", "http://x"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Config {
+                action: ConfigCommand::Set {
+                    key: "url".to_string(),
+                    value: "http://x".to_string(),
+                }
+            })
+        );
+
+        let args = Args::try_parse_from(["rsocr", "gui"]).unwrap();
+        assert!(!args.is_headless());
+
+        // ocr 子命令至少需要一个文件
+        assert!(Args::try_parse_from(["rsocr", "ocr"]).is_err());
+
+        let args = Args::try_parse_from(["rsocr", "ocr", "-"]).unwrap();
+        assert_eq!(
+            args.command,
+            Some(Command::Ocr {
+                files: vec!["-".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_ocr_args_merge() {
+        let args = Args::try_parse_from([
+            "rsocr",
+            "ocr",
+            "a.png",
+            "--cls",
+            "true",
+            "--parser",
+            "single_code",
+            "--ignore-area",
+            "0,0,100,20",
+        ])
+        .unwrap();
+
+        let config_options = OcrOptions {
+            language: Some("models/config_en.txt".to_string()),
+            cls: Some(false),
+            ..Default::default()
+        };
+        let options = args.ocr.merge(config_options);
+
+        assert_eq!(options.language, Some("models/config_en.txt".to_string()));
+        assert_eq!(options.cls, Some(true));
+        assert_eq!(options.parser, Some(TbpuParser::SingleCode));
+        assert_eq!(options.ignore_area, Some(vec![[[0, 0], [100, 20]]]));
+
+        assert!(Args::try_parse_from(["rsocr", "ocr", "a.png", "--ignore-area", "1,2"]).is_err());
+    }
+
+    #[test]
+    fn test_preprocess_args_apply() {
+        let mut config = PreprocessConfig {
+            steps: Some(vec![PreprocessStep::Grayscale]),
+            ..Default::default()
+        };
+
+        let args = Args::try_parse_from([
+            "rsocr",
+            "ocr",
+            "a.png",
+            "--preprocess",
+            "contrast,upscale",
+            "--upscale-factor",
+            "3",
+        ])
+        .unwrap();
+        args.preprocess.apply(&mut config);
+        assert_eq!(
+            config.steps,
+            Some(vec![PreprocessStep::Contrast, PreprocessStep::Upscale])
+        );
+        assert_eq!(config.upscale_factor, Some(3.0));
+
+        let args = Args::try_parse_from(["rsocr", "ocr", "a.png", "--no-preprocess"]).unwrap();
+        args.preprocess.apply(&mut config);
+        assert!(config.to_pipeline().is_empty());
+
+        let args = Args::try_parse_from([
+            "rsocr",
+            "ocr",
+            "a.png",
+            "--max-side",
+            "4000",
+            "--upload-format",
+            "jpeg",
+            "--jpeg-quality",
+            "70",
+        ])
+        .unwrap();
+        args.preprocess.apply(&mut config);
+        assert_eq!(config.max_side, Some(4000));
+        assert_eq!(config.format, Some(OutputFormat::Jpeg));
+        assert_eq!(config.jpeg_quality, Some(70));
+        assert_eq!(config.auto_rotate, None);
+
+        let args = Args::try_parse_from(["rsocr", "ocr", "a.png", "--auto-rotate"]).unwrap();
+        args.preprocess.apply(&mut config);
+        assert_eq!(config.auto_rotate, Some(true));
+        assert!(Args::try_parse_from(["rsocr", "ocr", "a.png", "--jpeg-quality", "0"]).is_err());
+
+        assert!(Args::try_parse_from(["rsocr", "ocr", "a.png", "--preprocess", "blur"]).is_err());
+    }
+
+    #[test]
+    fn test_pages() {
+        let args = Args::try_parse_from(["rsocr", "ocr", "fax.tiff", "--pages", "2-3,5"]).unwrap();
+        let pages = args.pages.unwrap();
+        assert!(pages.contains(3) && !pages.contains(4));
+        assert!(Args::try_parse_from(["rsocr", "ocr", "fax.tiff", "--pages", "3-1"]).is_err());
+
+        let page = |page: usize, text: &str| PageResult {
+            page,
+            result: crate::OcrResult {
+                blocks: vec![crate::TextBlock {
+                    text: text.to_string(),
+                    score: 1.0,
+                    bbox: [[0, 0]; 4],
+                    end: String::new(),
+                }],
+                ..Default::default()
+            },
+        };
+        assert_eq!(pages_text(&[page(1, "单页")]), "单页");
+        assert_eq!(pages_text(&[page(3, "三")]), "--- 第 3 页 ---\n三");
+        assert_eq!(
+            pages_text(&[page(1, "一"), page(2, "二")]),
+            "--- 第 1 页 ---\n一\n--- 第 2 页 ---\n二"
+        );
+    }
+
+    #[test]
+    fn test_run_batch_pages() -> Result<()> {
+        use crate::test_support::{MockResponse, MockServer};
+        use image::{DynamicImage, GrayImage, ImageFormat, Luma};
+
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let client = crate::OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let dir = tempfile::tempdir()?;
+        DynamicImage::ImageLuma8(GrayImage::from_pixel(4, 4, Luma([0])))
+            .save_with_format(dir.path().join("a.png"), ImageFormat::Png)
+            .unwrap();
+        let file = std::fs::File::create(dir.path().join("b.tiff"))?;
+        let mut encoder = tiff::encoder::TiffEncoder::new(file).unwrap();
+        for _ in 0..2 {
+            encoder
+                .write_image::<tiff::encoder::colortype::Gray8>(2, 2, &[255; 4])
+                .unwrap();
+        }
+        let dir = dir.path().to_string_lossy();
+
+        // 单页的PNG跳过，只识别TIFF的第2页
+        let code = run_batch(
+            &client,
+            &OcrOptions::default(),
+            &"2-".parse()?,
+            &dir,
+            false,
+            false,
+        )?;
+        assert_eq!(code, EXIT_SUCCESS);
+        assert_eq!(server.requests().len(), 1);
+
+        // 多页图片的页数不足时仍算作失败
+        let code = run_batch(
+            &client,
+            &OcrOptions::default(),
+            &"3".parse()?,
+            &dir,
+            false,
+            false,
+        )?;
+        assert_eq!(code, EXIT_OCR_FAILED);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(
+            exit_code(&RsOcrError::Config("缺少参数".to_string())),
+            EXIT_USAGE
+        );
+        assert_eq!(
+            exit_code(&RsOcrError::Io(std::io::Error::from(
+                std::io::ErrorKind::NotFound
+            ))),
+            EXIT_IO
+        );
+        assert_eq!(
+            exit_code(&RsOcrError::OcrApi("服务错误".to_string())),
+            EXIT_OCR_FAILED
+        );
+    }
+
+    #[test]
+    fn test_run_missing_file() {
+        let args = Args {
+            command: Some(Command::Ocr {
+                files: vec!["/non/existent/path/image.png".to_string()],
+            }),
+            file: None,
+            url: Some("http://127.0.0.1:1/api/ocr".to_string()),
+            backend: None,
+            save: false,
+            pages: None,
+            ocr: OcrArgs::default(),
+            preprocess: PreprocessArgs::default(),
+        };
+        assert_eq!(run_with_config(args, Config::default()), EXIT_IO);
+    }
+
+    #[test]
+    fn test_run_ocr_sources() -> Result<()> {
+        use crate::test_support::{MockResponse, MockServer};
+
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/scan.png" => MockResponse::text(200, "from-url"),
+            _ => MockResponse::umi_text("ok", 1.0),
+        });
+        let client = crate::OcrClient::new(format!("{}/api/ocr", server.url()))?;
+        let files = [
+            format!("{}/scan.png", server.url()),
+            "data:image/png;base64,aGVsbG8=".to_string(),
+        ];
+
+        // 下载图片时使用 `[client]` 的设置
+        let mut config = Config::default();
+        config.set("client.headers.X-Team", "ocr")?;
+
+        assert_eq!(
+            run_ocr(
+                &client,
+                &config,
+                &OcrOptions::default(),
+                &PageRange::all(),
+                &files
+            )?,
+            EXIT_SUCCESS
+        );
+        let requests = server.requests();
+        assert_eq!(requests[0].header("x-team"), Some("ocr"));
+        assert_eq!(requests[1].json()["base64"], "ZnJvbS11cmw=");
+        assert_eq!(requests[2].json()["base64"], "aGVsbG8=");
+
+        let err = run_ocr(
+            &client,
+            &config,
+            &OcrOptions::default(),
+            &PageRange::all(),
+            &["-".to_string(), "-".to_string()],
+        )
+        .unwrap_err();
+        assert!(matches!(err, RsOcrError::Config(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_unknown_backend() {
+        let args = Args::try_parse_from(["rsocr", "health", "--backend", "nope"]).unwrap();
+        assert_eq!(args.backend, Some("nope".to_string()));
+        assert_eq!(run_with_config(args, Config::default()), EXIT_USAGE);
+    }
 }
@@ -0,0 +1,914 @@
+//! 可复用的OCR客户端
+//!
+//! [`OcrClient`] 持有一个带连接池的HTTP客户端，批量识别时复用TCP连接，
+//! 并为每个请求设置连接超时和读取超时，避免服务器无响应时永久阻塞。
+//! API地址也可以是 `unix:///path/to/sock`，此时通过Unix域套接字发送请求。
+
+use crate::auth::Auth;
+use crate::capabilities::{self, ServerCapabilities};
+use crate::models::{
+    DataFormat, OcrOptions, OcrRequest, OcrResponse, OcrResult, Result, RsOcrError,
+};
+use crate::request_body::OcrRequestBody;
+use crate::retry::{self, CircuitBreaker, Outcome, RetryPolicy};
+use crate::unix_socket::{UnixTransport, is_unix_url};
+use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use reqwest::{Certificate, Identity, NoProxy, Proxy};
+use std::fmt;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 默认连接超时
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 默认读取超时（从发送请求到读取完响应的总时间）
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 默认的 User-Agent
+pub const DEFAULT_USER_AGENT: &str = concat!("rsOCR/", env!("CARGO_PKG_VERSION"));
+
+/// OCR客户端
+///
+/// 克隆 `OcrClient` 的开销很小，克隆出的实例共享同一个连接池。
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::OcrClient;
+/// use std::time::Duration;
+///
+/// fn main() -> rsocr::Result<()> {
+///     let client = OcrClient::builder("http://127.0.0.1:1224/api/ocr")
+///         .timeout(Duration::from_secs(30))
+///         .header("X-Request-Source", "batch")
+///         .build()?;
+///
+///     for path in ["a.png", "b.png"] {
+///         println!("{}", client.recognize_file(path)?.text());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OcrClient {
+    http: Client,
+    url: String,
+    /// API地址为 `unix://` 时使用的传输
+    unix: Option<UnixTransport>,
+    options: OcrOptions,
+    retry: RetryPolicy,
+    breaker: Option<CircuitBreaker>,
+}
+
+/// [`OcrClient`] 的构建器
+#[derive(Debug, Clone)]
+pub struct OcrClientBuilder {
+    url: String,
+    connect_timeout: Duration,
+    timeout: Option<Duration>,
+    user_agent: String,
+    headers: Vec<(String, String)>,
+    auth: Option<Auth>,
+    transport: Transport,
+    options: OcrOptions,
+    retry: RetryPolicy,
+    breaker: Option<CircuitBreaker>,
+}
+
+/// 代理和TLS设置
+#[derive(Clone, Default)]
+struct Transport {
+    proxy: Option<String>,
+    no_proxy: Vec<String>,
+    root_certificates: Vec<Vec<u8>>,
+    /// 客户端证书和PKCS#8私钥（PEM）
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+    accept_invalid_certs: bool,
+}
+
+impl Transport {
+    fn proxy(&self) -> Result<Option<Proxy>> {
+        let Some(url) = &self.proxy else {
+            return Ok(None);
+        };
+        let proxy =
+            Proxy::all(url).map_err(|e| RsOcrError::Config(format!("无效的代理地址: {}", e)))?;
+        Ok(Some(if self.no_proxy.is_empty() {
+            proxy
+        } else {
+            proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")))
+        }))
+    }
+
+    fn root_certificates(&self) -> Result<Vec<Certificate>> {
+        self.root_certificates
+            .iter()
+            .map(|pem| {
+                Certificate::from_pem(pem)
+                    .map_err(|e| RsOcrError::Config(format!("无效的CA证书: {}", e)))
+            })
+            .collect()
+    }
+
+    fn identity(&self) -> Result<Option<Identity>> {
+        self.identity
+            .as_ref()
+            .map(|(cert, key)| {
+                Identity::from_pkcs8_pem(cert, key)
+                    .map_err(|e| RsOcrError::Config(format!("无效的客户端证书或私钥: {}", e)))
+            })
+            .transpose()
+    }
+}
+
+impl fmt::Debug for Transport {
+    /// 不输出代理地址中的用户名密码和客户端私钥
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proxy = self.proxy.as_ref().map(|url| match url.rsplit_once('@') {
+            Some((_, host)) => format!("***@{}", host),
+            None => url.clone(),
+        });
+        f.debug_struct("Transport")
+            .field("proxy", &proxy)
+            .field("no_proxy", &self.no_proxy)
+            .field("root_certificates", &self.root_certificates.len())
+            .field("identity", &self.identity.is_some())
+            .field("accept_invalid_certs", &self.accept_invalid_certs)
+            .finish()
+    }
+}
+
+impl OcrClientBuilder {
+    /// 创建构建器
+    ///
+    /// # 参数
+    /// * `url` - OCR API的URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            timeout: Some(DEFAULT_TIMEOUT),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+            auth: None,
+            transport: Transport::default(),
+            options: OcrOptions::default(),
+            retry: RetryPolicy::none(),
+            breaker: None,
+        }
+    }
+
+    /// 设置连接超时
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// 设置读取超时，`None` 表示不限制
+    pub fn timeout(mut self, timeout: impl Into<Option<Duration>>) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// 设置 User-Agent
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// 添加每个请求都会发送的请求头
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// 设置认证方式，密钥在构建时解析
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// 通过代理发送全部请求，如 `http://proxy.corp:3128`
+    ///
+    /// 未设置时使用 `HTTP_PROXY`、`HTTPS_PROXY` 和 `NO_PROXY` 环境变量。
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.transport.proxy = Some(url.into());
+        self
+    }
+
+    /// 设置不经过 [`proxy`](Self::proxy) 的主机，支持域名后缀、IP和CIDR
+    pub fn no_proxy<I, S>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.transport.no_proxy = hosts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 信任额外的根证书（PEM），系统证书仍然有效
+    pub fn add_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.transport.root_certificates.push(pem.into());
+        self
+    }
+
+    /// 设置双向TLS使用的客户端证书
+    ///
+    /// # 参数
+    /// * `cert` - 证书链（PEM）
+    /// * `key` - PKCS#8 格式的私钥（PEM，`BEGIN PRIVATE KEY`）
+    pub fn client_identity(mut self, cert: impl Into<Vec<u8>>, key: impl Into<Vec<u8>>) -> Self {
+        self.transport.identity = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// 不校验服务器证书，只应用于测试环境
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.transport.accept_invalid_certs = accept;
+        self
+    }
+
+    /// 设置默认的OCR请求选项
+    pub fn options(mut self, options: OcrOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// 设置识别请求的重试策略，默认不重试
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// 设置熔断器，默认不启用
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// 构建客户端
+    ///
+    /// # 返回
+    /// * `Result<OcrClient>` - 请求头、代理或证书无效，或密钥无法解析时返回 [`RsOcrError::Config`]
+    pub fn build(self) -> Result<OcrClient> {
+        let headers = self.header_map()?;
+        let unix = if is_unix_url(&self.url) {
+            let mut headers = headers.clone();
+            headers.insert(
+                USER_AGENT,
+                HeaderValue::from_str(&self.user_agent)
+                    .map_err(|e| RsOcrError::Config(format!("无效的 User-Agent: {}", e)))?,
+            );
+            Some(UnixTransport::new(&self.url, headers, self.timeout)?)
+        } else {
+            None
+        };
+
+        let mut builder = Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.timeout)
+            .user_agent(&self.user_agent)
+            .default_headers(headers)
+            .danger_accept_invalid_certs(self.accept_invalid_certs());
+        if let Some(proxy) = self.transport.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.transport.root_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = self.transport.identity()? {
+            builder = builder.identity(identity);
+        }
+        let http = builder.build().map_err(RsOcrError::Http)?;
+
+        Ok(OcrClient {
+            http,
+            url: self.url,
+            unix,
+            options: self.options,
+            retry: self.retry,
+            breaker: self.breaker,
+        })
+    }
+
+    /// 构建异步客户端
+    ///
+    /// # 返回
+    /// * `Result<AsyncOcrClient>` - 请求头、代理或证书无效，密钥无法解析，
+    ///   或API地址是 `unix://` 地址时返回 [`RsOcrError::Config`]
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<crate::async_client::AsyncOcrClient> {
+        if is_unix_url(&self.url) {
+            return Err(RsOcrError::Config(format!(
+                "异步客户端不支持Unix域套接字地址: {}",
+                self.url
+            )));
+        }
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .user_agent(&self.user_agent)
+            .default_headers(self.header_map()?)
+            .danger_accept_invalid_certs(self.accept_invalid_certs());
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = self.transport.proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in self.transport.root_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(identity) = self.transport.identity()? {
+            builder = builder.identity(identity);
+        }
+        let http = builder.build().map_err(RsOcrError::Http)?;
+
+        Ok(crate::async_client::AsyncOcrClient::from_parts(
+            http,
+            self.url,
+            self.options,
+            self.retry,
+            self.breaker,
+        ))
+    }
+
+    fn accept_invalid_certs(&self) -> bool {
+        if self.transport.accept_invalid_certs {
+            log::warn!("已关闭服务器证书校验: {}", self.url);
+        }
+        self.transport.accept_invalid_certs
+    }
+
+    fn header_map(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| RsOcrError::Config(format!("无效的请求头名称 '{}': {}", name, e)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| RsOcrError::Config(format!("无效的请求头 '{}': {}", name, e)))?;
+            headers.append(name, value);
+        }
+        if let Some(auth) = &self.auth {
+            let (name, value) = auth.header()?;
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// 构造请求体，总是以 [`DataFormat::Dict`] 格式请求
+pub(crate) fn request_body(base64_code: &str, options: &OcrOptions) -> OcrRequest {
+    OcrRequest {
+        base64: base64_code.to_string(),
+        options: dict_options(options),
+    }
+}
+
+/// 总是请求 [`DataFormat::Dict`] 格式的结果
+fn dict_options(options: &OcrOptions) -> OcrOptions {
+    OcrOptions {
+        data_format: DataFormat::Dict,
+        ..options.clone()
+    }
+}
+
+/// 检查识别请求的HTTP状态码
+pub(crate) fn check_status(status: reqwest::StatusCode) -> Result<()> {
+    if status.is_success() {
+        return Ok(());
+    }
+    Err(RsOcrError::OcrApi(format!(
+        "OCR API请求失败: HTTP状态码 {} ({})",
+        status,
+        status.canonical_reason().unwrap_or("未知错误")
+    )))
+}
+
+/// 检查健康检查请求的HTTP状态码，只有服务器错误（5xx）视为不可用
+pub(crate) fn check_health_status(status: reqwest::StatusCode) -> Result<()> {
+    if !status.is_server_error() {
+        return Ok(());
+    }
+    Err(RsOcrError::OcrApi(format!(
+        "OCR服务异常: HTTP状态码 {} ({})",
+        status,
+        status.canonical_reason().unwrap_or("未知错误")
+    )))
+}
+
+/// 兼容函数（如 [`call_ocr_api`](crate::call_ocr_api)）共享的HTTP客户端
+pub(crate) fn shared_http_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        Client::builder()
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .timeout(DEFAULT_TIMEOUT)
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .unwrap_or_else(|_| Client::new())
+    })
+}
+
+impl OcrClient {
+    /// 创建构建器
+    ///
+    /// # 参数
+    /// * `url` - OCR API的URL
+    pub fn builder(url: impl Into<String>) -> OcrClientBuilder {
+        OcrClientBuilder::new(url)
+    }
+
+    /// 使用默认设置创建客户端
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        Self::builder(url).build()
+    }
+
+    /// 使用共享的HTTP客户端创建客户端
+    ///
+    /// # 返回
+    /// * `Result<Self>` - `unix://` 地址格式错误时返回 [`RsOcrError::Config`]
+    pub(crate) fn shared(url: &str) -> Result<Self> {
+        let unix = if is_unix_url(url) {
+            let mut headers = HeaderMap::new();
+            headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+            Some(UnixTransport::new(url, headers, Some(DEFAULT_TIMEOUT))?)
+        } else {
+            None
+        };
+        Ok(Self {
+            http: shared_http_client().clone(),
+            url: url.to_string(),
+            unix,
+            options: OcrOptions::default(),
+            retry: RetryPolicy::none(),
+            breaker: None,
+        })
+    }
+
+    /// OCR API的URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 底层的HTTP客户端，供其他HTTP后端复用连接池和请求头设置
+    ///
+    /// 它不支持 `unix://` 地址。
+    pub(crate) fn http(&self) -> &Client {
+        &self.http
+    }
+
+    /// 识别请求的重试策略
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// 熔断器，未启用时为 `None`
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.breaker.as_ref()
+    }
+
+    /// 默认的OCR请求选项
+    pub fn options(&self) -> &OcrOptions {
+        &self.options
+    }
+
+    /// 使用默认选项识别base64编码的图片
+    pub fn recognize(&self, base64_code: &str) -> Result<OcrResult> {
+        self.recognize_with_options(base64_code, &self.options)
+    }
+
+    /// 使用指定选项识别base64编码的图片
+    ///
+    /// 为了得到结构化结果，请求总是以 [`DataFormat::Dict`] 格式发送，
+    /// 忽略 `options.data_format` 的值。连接失败、超时或可重试的状态码
+    /// 按 [`RetryPolicy`] 重试；启用熔断器时，熔断期间直接返回 [`RsOcrError::CircuitOpen`]。
+    pub fn recognize_with_options(
+        &self,
+        base64_code: &str,
+        options: &OcrOptions,
+    ) -> Result<OcrResult> {
+        let body = request_body(base64_code, options);
+        if let Some(unix) = &self.unix {
+            let payload = serde_json::to_vec(&body)?;
+            let response = retry::run(&self.retry, self.breaker.as_ref(), || {
+                let response = unix.send("POST", unix.path(), Some(&payload));
+                Outcome::classify_io(&self.retry, response, |r| r.status)
+            })?;
+            check_status(response.status_code()?)?;
+
+            let ocr_response: OcrResponse = serde_json::from_slice(&response.body)?;
+            return ocr_response.into_result();
+        }
+
+        let response = retry::run(&self.retry, self.breaker.as_ref(), || {
+            let response = self
+                .http
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .json(&body)
+                .send();
+            Outcome::classify(&self.retry, response, |r| r.status().as_u16())
+        })?;
+        check_status(response.status())?;
+
+        let ocr_response: OcrResponse = response.json().map_err(RsOcrError::Http)?;
+        ocr_response.into_result()
+    }
+
+    /// 使用默认选项识别图片文件
+    pub fn recognize_file(&self, image_path: &str) -> Result<OcrResult> {
+        self.recognize_file_with_options(image_path, &self.options)
+    }
+
+    /// 使用指定选项识别图片文件
+    ///
+    /// 请求体由 [`OcrRequestBody`] 边读取文件边编码生成，不会把整个文件及其base64编码
+    /// 读入内存。每次重试都会重新打开文件。
+    pub fn recognize_file_with_options(
+        &self,
+        image_path: &str,
+        options: &OcrOptions,
+    ) -> Result<OcrResult> {
+        let options = dict_options(options);
+        // 文件无法打开时直接返回，不计入重试和熔断
+        let mut first = Some(OcrRequestBody::from_file(image_path, &options)?);
+        let mut body = || match first.take() {
+            Some(body) => Ok(body),
+            None => OcrRequestBody::from_file(image_path, &options),
+        };
+
+        if let Some(unix) = &self.unix {
+            let response = retry::run(&self.retry, self.breaker.as_ref(), || match body() {
+                Ok(body) => {
+                    let length = body.content_length();
+                    let response = unix.send_reader("POST", unix.path(), body, length);
+                    Outcome::classify_io(&self.retry, response, |r| r.status)
+                }
+                Err(e) => Outcome::Done(Err(e)),
+            })?;
+            check_status(response.status_code()?)?;
+
+            let ocr_response: OcrResponse = serde_json::from_slice(&response.body)?;
+            return ocr_response.into_result();
+        }
+
+        let response = retry::run(&self.retry, self.breaker.as_ref(), || {
+            let body = match body() {
+                Ok(body) => body,
+                Err(e) => return Outcome::Done(Err(e)),
+            };
+            let response = self
+                .http
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .body(body)
+                .send();
+            Outcome::classify(&self.retry, response, |r| r.status().as_u16())
+        })?;
+        check_status(response.status())?;
+
+        let ocr_response: OcrResponse = response.json().map_err(RsOcrError::Http)?;
+        ocr_response.into_result()
+    }
+
+    /// 检查OCR服务是否可访问
+    ///
+    /// 向API地址发送GET请求，只要服务有响应且不是服务器错误（5xx）即视为可用。
+    pub fn health(&self) -> Result<()> {
+        if let Some(unix) = &self.unix {
+            let response = unix.send("GET", unix.path(), None)?;
+            return check_health_status(response.status_code()?);
+        }
+        let response = self.http.get(&self.url).send().map_err(RsOcrError::Http)?;
+        check_health_status(response.status())
+    }
+
+    /// 查询服务器支持的OCR参数，结果按API地址缓存
+    pub fn capabilities(&self) -> Result<ServerCapabilities> {
+        capabilities::get_or_fetch(&self.url, || self.fetch_capabilities())
+    }
+
+    /// 向服务器查询支持的OCR参数（不使用缓存）
+    pub(crate) fn fetch_capabilities(&self) -> Result<ServerCapabilities> {
+        let json: serde_json::Value = match &self.unix {
+            Some(unix) => {
+                let response = unix.send("GET", &capabilities::options_url(unix.path()), None)?;
+                capabilities::check_status(response.status_code()?)?;
+                serde_json::from_slice(&response.body)?
+            }
+            None => {
+                let response = self
+                    .http
+                    .get(capabilities::options_url(&self.url))
+                    .send()
+                    .map_err(RsOcrError::Http)?;
+                capabilities::check_status(response.status())?;
+                response.json().map_err(RsOcrError::Http)?
+            }
+        };
+        Ok(ServerCapabilities::from_json(&json))
+    }
+
+    /// 发送请求前校验OCR请求选项，见 [`capabilities::validate_options`]
+    pub fn validate_options(&self, options: &OcrOptions) -> Result<()> {
+        if !capabilities::needs_validation(options) {
+            return Ok(());
+        }
+        capabilities::validate_against(self.capabilities(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::SecretRef;
+    use crate::test_support::{MockResponse, MockServer, tls_fixture};
+
+    #[test]
+    fn test_builder_headers_and_user_agent() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .user_agent("rsocr-test")
+            .header("X-Trace", "abc")
+            .options(OcrOptions {
+                cls: Some(true),
+                ..Default::default()
+            })
+            .build()?;
+
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("user-agent"), Some("rsocr-test"));
+        assert_eq!(request.header("x-trace"), Some("abc"));
+        assert_eq!(request.json()["options"]["ocr.cls"], true);
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_auth() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let builder = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .auth(Auth::Bearer(SecretRef::Plain("s3cr3t".to_string())));
+        assert!(!format!("{:?}", builder).contains("s3cr3t"));
+
+        let client = builder.build()?;
+        assert!(!format!("{:?}", client).contains("s3cr3t"));
+        client.recognize("aGVsbG8=")?;
+        assert_eq!(
+            server.requests()[0].header("authorization"),
+            Some("Bearer s3cr3t")
+        );
+
+        let result = OcrClient::builder(server.url())
+            .auth(Auth::Bearer(SecretRef::Env(
+                "RSOCR_TEST_TOKEN_MISSING".to_string(),
+            )))
+            .build();
+        assert!(matches!(result, Err(RsOcrError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tls_root_certificate() -> Result<()> {
+        let server = MockServer::start_tls(false, |_| MockResponse::umi_text("ok", 1.0));
+        let url = format!("{}/api/ocr", server.url());
+
+        // 测试CA不在系统证书中
+        assert!(OcrClient::new(&url)?.recognize("aGVsbG8=").is_err());
+
+        let ca = std::fs::read(tls_fixture("ca.pem"))?;
+        let client = OcrClient::builder(&url).add_root_certificate(ca).build()?;
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+
+        let client = OcrClient::builder(&url)
+            .danger_accept_invalid_certs(true)
+            .build()?;
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+
+        let result = OcrClient::builder(&url)
+            .add_root_certificate("not a pem")
+            .build();
+        assert!(matches!(result, Err(RsOcrError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mutual_tls() -> Result<()> {
+        let server = MockServer::start_tls(true, |_| MockResponse::umi_text("ok", 1.0));
+        let url = format!("{}/api/ocr", server.url());
+        let ca = std::fs::read(tls_fixture("ca.pem"))?;
+
+        let client = OcrClient::builder(&url)
+            .add_root_certificate(ca.clone())
+            .build()?;
+        assert!(client.recognize("aGVsbG8=").is_err());
+
+        let builder = OcrClient::builder(&url)
+            .add_root_certificate(ca)
+            .client_identity(
+                std::fs::read(tls_fixture("client.pem"))?,
+                std::fs::read(tls_fixture("client.key"))?,
+            );
+        assert!(!format!("{:?}", builder).contains("PRIVATE KEY"));
+        assert_eq!(builder.build()?.recognize("aGVsbG8=")?.text(), "ok");
+        assert_eq!(server.requests().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_proxy() -> Result<()> {
+        let proxy = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let builder = OcrClient::builder("http://ocr.invalid/api/ocr")
+            .proxy(format!("http://user:pass@{}", proxy.addr()));
+        assert!(!format!("{:?}", builder).contains("pass"));
+        builder.build()?.recognize("aGVsbG8=")?;
+
+        let request = &proxy.requests()[0];
+        assert_eq!(request.path, "http://ocr.invalid/api/ocr");
+        assert!(request.header("proxy-authorization").is_some());
+
+        // no_proxy 中的主机直接连接
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .proxy("http://127.0.0.1:1")
+            .no_proxy(["127.0.0.1"])
+            .build()?;
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let result = OcrClient::builder("http://127.0.0.1:1/api/ocr")
+            .header("bad header", "value")
+            .build();
+        assert!(matches!(result, Err(RsOcrError::Config(_))));
+    }
+
+    #[test]
+    fn test_connection_reuse() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::umi_text("ok", 1.0));
+        let client = OcrClient::new(format!("{}/api/ocr", server.url()))?;
+
+        for _ in 0..3 {
+            client.recognize("aGVsbG8=")?;
+        }
+
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(server.connections(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_timeout() -> Result<()> {
+        let server = MockServer::start(|_| {
+            std::thread::sleep(Duration::from_millis(1500));
+            MockResponse::umi_text("ok", 1.0)
+        });
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .timeout(Duration::from_millis(200))
+            .build()?;
+
+        let started = std::time::Instant::now();
+        let err = client.recognize("aGVsbG8=").unwrap_err();
+        assert!(matches!(err, RsOcrError::Http(ref e) if e.is_timeout()));
+        assert!(started.elapsed() < Duration::from_millis(1500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_on_unavailable() -> Result<()> {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&calls);
+        let server = MockServer::start(move |_| {
+            match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0 | 1 => MockResponse::text(503, "restarting"),
+                _ => MockResponse::umi_text("ok", 1.0),
+            }
+        });
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .retry(policy.clone())
+            .build()?;
+
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+        assert_eq!(server.requests().len(), 3);
+
+        // 流式请求体在每次重试时重新读取文件
+        calls.store(0, std::sync::atomic::Ordering::SeqCst);
+        let mut image = tempfile::NamedTempFile::new()?;
+        std::io::Write::write_all(&mut image, b"hello")?;
+        let path = image.path().to_str().unwrap();
+        assert_eq!(client.recognize_file(path)?.text(), "ok");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 6);
+        for request in &requests[3..] {
+            assert_eq!(request.json()["base64"], "aGVsbG8=");
+            assert_eq!(request.json()["options"]["data.format"], "dict");
+        }
+        assert!(matches!(
+            client.recognize_file("/nonexistent/image.png"),
+            Err(RsOcrError::Io(_))
+        ));
+        assert_eq!(server.requests().len(), 6);
+
+        // 次数用完后返回最后一次的状态码错误；不可重试的状态码不重试
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/busy" => MockResponse::text(503, "busy"),
+            _ => MockResponse::text(400, "bad"),
+        });
+        let client = OcrClient::builder(format!("{}/busy", server.url()))
+            .retry(policy.clone())
+            .build()?;
+        assert!(matches!(client.recognize("x"), Err(RsOcrError::OcrApi(_))));
+        assert_eq!(server.requests().len(), 3);
+
+        let client = OcrClient::builder(format!("{}/bad", server.url()))
+            .retry(policy)
+            .build()?;
+        assert!(client.recognize("x").is_err());
+        assert_eq!(server.requests().len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit_breaker_fails_fast() -> Result<()> {
+        let server = MockServer::start(|_| MockResponse::text(503, "down"));
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+            .build()?;
+
+        assert!(matches!(client.recognize("x"), Err(RsOcrError::OcrApi(_))));
+        assert!(matches!(client.recognize("x"), Err(RsOcrError::OcrApi(_))));
+        assert!(matches!(
+            client.clone().recognize("x"),
+            Err(RsOcrError::CircuitOpen(_))
+        ));
+        assert_eq!(server.requests().len(), 2);
+
+        // 重试的请求只计一次失败
+        let client = OcrClient::builder(format!("{}/api/ocr", server.url()))
+            .retry(RetryPolicy {
+                initial_backoff: Duration::from_millis(1),
+                ..Default::default()
+            })
+            .circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+            .build()?;
+        assert!(matches!(client.recognize("x"), Err(RsOcrError::OcrApi(_))));
+        assert_eq!(server.requests().len(), 5);
+        assert!(!client.circuit_breaker().unwrap().is_open());
+        assert!(matches!(client.recognize("x"), Err(RsOcrError::OcrApi(_))));
+        assert!(client.circuit_breaker().unwrap().is_open());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_socket() -> Result<()> {
+        let server = MockServer::start_unix(|request| match request.path.as_str() {
+            "/api/ocr/get_options" => MockResponse::json(serde_json::json!({
+                "ocr.language": {"default": "zh", "optionsList": [["zh", "简体中文"]]}
+            })),
+            _ => MockResponse::umi_text("ok", 1.0),
+        });
+        let url = server.url();
+        assert!(url.starts_with("unix:///"));
+
+        let client = OcrClient::builder(&url).header("X-Trace", "unix").build()?;
+        client.health()?;
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "ok");
+        assert_eq!(client.capabilities()?.languages.len(), 1);
+        assert_eq!(
+            crate::ocr_utils::call_ocr_api("aGVsbG8=", &url)?.text(),
+            "ok"
+        );
+        let mut image = tempfile::NamedTempFile::new()?;
+        std::io::Write::write_all(&mut image, b"hello")?;
+        assert_eq!(
+            client
+                .recognize_file(image.path().to_str().unwrap())?
+                .text(),
+            "ok"
+        );
+        assert_eq!(server.requests()[4].json()["base64"], "aGVsbG8=");
+
+        let requests = server.requests();
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].path, "/api/ocr");
+        assert_eq!(requests[1].header("X-Trace"), Some("unix"));
+        assert_eq!(requests[1].json()["base64"], "aGVsbG8=");
+        assert_eq!(requests[2].path, "/api/ocr/get_options");
+
+        // 套接字不存在时按连接错误处理
+        let missing = OcrClient::builder(format!("{}.missing", url))
+            .retry(RetryPolicy::none())
+            .build()?;
+        assert!(matches!(missing.recognize("x"), Err(RsOcrError::Io(_))));
+        Ok(())
+    }
+}
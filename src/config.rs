@@ -1,14 +1,465 @@
-use crate::models::{Result, RsOcrError};
+use crate::auth::{Auth, DEFAULT_API_KEY_HEADER, SecretRef};
+use crate::backend::{
+    BaiduBackend, BaiduModel, BodyKind, DEFAULT_BACKEND, DEFAULT_BAIDU_URL, DEFAULT_GOOGLE_URL,
+    DEFAULT_LLM_URL, DEFAULT_PADDLE_URL, DEFAULT_TESSERACT, GoogleFeature, GoogleVisionBackend,
+    HttpBackend, LlmBackend, PaddleBackend, ResponseMapping, TesseractBackend, TesseractFormat,
+};
+use crate::client::{OcrClient, OcrClientBuilder};
+use crate::models::{IgnoreArea, OcrOptions, Result, RsOcrError, TbpuParser};
+use crate::preprocess::{OutputFormat, Pipeline, PreprocessStep};
+use crate::retry::{CircuitBreaker, RetryPolicy};
+use crate::unix_socket::is_unix_url;
 use log::error;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 默认的OCR API地址
+pub const DEFAULT_URL: &str = "http://127.0.0.1:1224/api/ocr";
 
 /// 配置结构体
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
     pub file: Option<String>,
     pub url: Option<String>,
+
+    /// OCR后端名称，未设置时使用 [`DEFAULT_BACKEND`]
+    pub backend: Option<String>,
+
+    /// OCR识别参数
+    #[serde(default)]
+    pub ocr: OcrConfig,
+
+    /// 上传前的图片预处理
+    #[serde(default)]
+    pub preprocess: PreprocessConfig,
+
+    /// HTTP客户端设置
+    #[serde(default)]
+    pub client: ClientConfig,
+
+    /// 认证设置
+    #[serde(default)]
+    pub auth: AuthConfig,
+
+    /// 识别请求的重试策略，没有 `[retry]` 表时不重试
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+
+    /// 熔断器设置
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+
+    /// Tesseract后端设置
+    #[serde(default)]
+    pub tesseract: TesseractConfig,
+
+    /// 通用HTTP后端设置
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// PaddleOCR后端设置
+    #[serde(default)]
+    pub paddle: PaddleConfig,
+
+    /// 视觉大模型后端设置
+    #[serde(default)]
+    pub llm: LlmConfig,
+
+    /// Google Cloud Vision 后端设置
+    #[serde(default)]
+    pub google: GoogleConfig,
+
+    /// 百度文字识别后端设置
+    #[serde(default)]
+    pub baidu: BaiduConfig,
+}
+
+/// 配置文件中的HTTP客户端设置（`[client]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ClientConfig {
+    /// 连接超时（秒）
+    pub connect_timeout: Option<u64>,
+    /// 读取超时（秒），0 表示不限制
+    pub timeout: Option<u64>,
+    /// User-Agent
+    pub user_agent: Option<String>,
+    /// 每个请求都会发送的请求头
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// 代理地址，未设置时使用 `HTTP_PROXY`/`HTTPS_PROXY` 环境变量
+    pub proxy: Option<String>,
+    /// 不经过代理的主机
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// 额外信任的CA证书文件（PEM）
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+    /// 双向TLS的客户端证书文件（PEM）
+    pub client_cert: Option<PathBuf>,
+    /// 双向TLS的客户端私钥文件（PKCS#8 PEM）
+    pub client_key: Option<PathBuf>,
+    /// 不校验服务器证书，只应用于测试环境
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+}
+
+impl ClientConfig {
+    /// 设置代理和TLS，读取证书文件
+    fn apply_transport(&self, mut builder: OcrClientBuilder) -> Result<OcrClientBuilder> {
+        let read = |path: &PathBuf| {
+            fs::read(path).map_err(|e| {
+                RsOcrError::Config(format!("无法读取证书文件 {}: {}", path.display(), e))
+            })
+        };
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy).no_proxy(self.no_proxy.clone());
+        }
+        for path in &self.ca_certs {
+            builder = builder.add_root_certificate(read(path)?);
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => builder = builder.client_identity(read(cert)?, read(key)?),
+            (None, None) => {}
+            _ => {
+                return Err(RsOcrError::Config(
+                    "client.client_cert 和 client.client_key 必须同时设置".to_string(),
+                ));
+            }
+        }
+        Ok(builder.danger_accept_invalid_certs(self.accept_invalid_certs))
+    }
+}
+
+/// 认证方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthKind {
+    /// `Authorization: Bearer`
+    Bearer,
+    /// `Authorization: Basic`
+    Basic,
+    /// 在 `header`（默认 `X-API-Key`）中发送密钥
+    ApiKey,
+}
+
+/// 配置文件中的认证设置（`[auth]` 表）
+///
+/// 密钥写作 `env:NAME` 或 `file:PATH` 引用，见 [`SecretRef`]。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AuthConfig {
+    /// 认证方式，未设置时不认证
+    #[serde(rename = "type")]
+    pub kind: Option<AuthKind>,
+    /// bearer 的令牌或 api_key 的密钥
+    pub token: Option<SecretRef>,
+    /// basic 的用户名
+    pub username: Option<String>,
+    /// basic 的密码
+    pub password: Option<SecretRef>,
+    /// api_key 使用的请求头
+    pub header: Option<String>,
+}
+
+impl AuthConfig {
+    /// 转换为 [`Auth`]
+    ///
+    /// # 返回
+    /// * `Result<Option<Auth>>` - 未设置认证方式时返回 `None`，缺少必需的项时返回 [`RsOcrError::Config`]
+    pub fn to_auth(&self) -> Result<Option<Auth>> {
+        let missing = |key: &str| RsOcrError::Config(format!("认证配置缺少 auth.{}", key));
+        let Some(kind) = self.kind else {
+            return Ok(None);
+        };
+
+        Ok(Some(match kind {
+            AuthKind::Bearer => Auth::Bearer(self.token.clone().ok_or_else(|| missing("token"))?),
+            AuthKind::Basic => Auth::Basic {
+                username: self.username.clone().ok_or_else(|| missing("username"))?,
+                password: self.password.clone().ok_or_else(|| missing("password"))?,
+            },
+            AuthKind::ApiKey => Auth::ApiKey {
+                header: self
+                    .header
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_API_KEY_HEADER.to_string()),
+                key: self.token.clone().ok_or_else(|| missing("token"))?,
+            },
+        }))
+    }
+}
+
+/// 配置文件中的重试策略（`[retry]` 表）
+///
+/// 没有这个表时不重试；表中未设置的项使用 [`RetryPolicy::default`] 的值，
+/// `max_attempts = 1` 表示不重试。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RetryConfig {
+    /// 最多尝试的次数（包括第一次请求）
+    pub max_attempts: Option<u32>,
+    /// 第一次重试前的等待时间（毫秒）
+    pub initial_backoff_ms: Option<u64>,
+    /// 等待时间的上限（毫秒）
+    pub max_backoff_ms: Option<u64>,
+    /// 每次重试后等待时间的倍数
+    pub multiplier: Option<f64>,
+    /// 随机抖动比例（0 ~ 1）
+    pub jitter: Option<f64>,
+    /// 可重试的HTTP状态码
+    pub status: Option<Vec<u16>>,
+    /// 连接失败时是否重试
+    pub connect_errors: Option<bool>,
+    /// 请求超时时是否重试
+    pub timeouts: Option<bool>,
+}
+
+impl RetryConfig {
+    /// 转换为重试策略
+    pub fn to_policy(&self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            max_attempts: self.max_attempts.unwrap_or(default.max_attempts),
+            initial_backoff: self
+                .initial_backoff_ms
+                .map_or(default.initial_backoff, Duration::from_millis),
+            max_backoff: self
+                .max_backoff_ms
+                .map_or(default.max_backoff, Duration::from_millis),
+            multiplier: self.multiplier.unwrap_or(default.multiplier),
+            jitter: self.jitter.unwrap_or(default.jitter),
+            retry_status: self.status.clone().unwrap_or(default.retry_status),
+            retry_connect: self.connect_errors.unwrap_or(default.retry_connect),
+            retry_timeout: self.timeouts.unwrap_or(default.retry_timeout),
+        }
+    }
+}
+
+/// 配置文件中的熔断器设置（`[circuit_breaker]` 表）
+///
+/// 未设置 `failure_threshold` 或设为 0 时不启用熔断器。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CircuitBreakerConfig {
+    /// 连续失败多少次后熔断
+    pub failure_threshold: Option<u32>,
+    /// 熔断后等待多久再探测（秒），默认 30
+    pub cool_down: Option<u64>,
+}
+
+impl CircuitBreakerConfig {
+    /// 默认的熔断冷却时间（秒）
+    pub const DEFAULT_COOL_DOWN: u64 = 30;
+
+    /// 转换为熔断器，未启用时返回 `None`
+    pub fn to_breaker(&self) -> Option<CircuitBreaker> {
+        let threshold = self.failure_threshold.filter(|&n| n > 0)?;
+        let cool_down = self.cool_down.unwrap_or(Self::DEFAULT_COOL_DOWN);
+        Some(CircuitBreaker::new(
+            threshold,
+            Duration::from_secs(cool_down),
+        ))
+    }
+}
+
+/// 配置文件中的Tesseract后端设置（`[tesseract]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TesseractConfig {
+    /// 可执行文件路径，默认为 `tesseract`
+    pub path: Option<String>,
+    /// 识别语言，如 `["chi_sim", "eng"]`
+    pub languages: Option<Vec<String>>,
+    /// 页面分割模式（`--psm`）
+    pub psm: Option<u8>,
+    /// 单次识别的超时时间（秒）
+    pub timeout: Option<u64>,
+    /// 输出格式：tsv 或 hocr
+    pub format: Option<TesseractFormat>,
+}
+
+impl TesseractConfig {
+    /// 转换为Tesseract后端
+    pub fn to_backend(&self) -> TesseractBackend {
+        let mut backend = TesseractBackend::new(self.path.as_deref().unwrap_or(DEFAULT_TESSERACT))
+            .languages(self.languages.clone().unwrap_or_default())
+            .format(self.format.unwrap_or_default());
+        if let Some(psm) = self.psm {
+            backend = backend.psm(psm);
+        }
+        if let Some(secs) = self.timeout {
+            backend = backend.timeout(Duration::from_secs(secs));
+        }
+        backend
+    }
+}
+
+/// 配置文件中的通用HTTP后端设置（`[http]` 表）
+///
+/// 超时、请求头、重试和熔断设置与Umi-OCR后端共用 `[client]`、`[retry]` 和 `[circuit_breaker]` 表。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HttpConfig {
+    /// 服务地址，未设置时使用顶层的 `url`
+    pub url: Option<String>,
+    /// 请求体类型：json 或 multipart
+    pub body: Option<BodyKind>,
+    /// JSON请求体模板
+    pub template: Option<String>,
+    /// multipart 上传时图片文件字段的名称，默认为 `file`
+    pub file_field: Option<String>,
+    /// multipart 上传时的其他文本字段
+    #[serde(default)]
+    pub form: BTreeMap<String, String>,
+    /// 响应映射
+    #[serde(default)]
+    pub response: ResponseMapping,
+}
+
+/// 配置文件中的PaddleOCR后端设置（`[paddle]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PaddleConfig {
+    /// 服务地址，默认为 [`DEFAULT_PADDLE_URL`]
+    pub url: Option<String>,
+}
+
+/// 配置文件中的视觉大模型后端设置（`[llm]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LlmConfig {
+    /// OpenAI兼容的 chat completions 地址，默认为 [`DEFAULT_LLM_URL`]
+    pub url: Option<String>,
+    /// 模型名称（必填）
+    pub model: Option<String>,
+    /// 转写提示词
+    pub prompt: Option<String>,
+    /// 最大生成长度
+    pub max_tokens: Option<u32>,
+    /// 是否要求模型输出带坐标的JSON
+    #[serde(default)]
+    pub json_boxes: bool,
+}
+
+/// 配置文件中的 Google Cloud Vision 后端设置（`[google]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GoogleConfig {
+    /// 接口根地址，默认为 [`DEFAULT_GOOGLE_URL`]
+    pub url: Option<String>,
+    /// API Key
+    pub api_key: Option<SecretRef>,
+    /// OAuth访问令牌，与 `api_key` 二选一；通常1小时后过期，不会自动刷新
+    pub access_token: Option<SecretRef>,
+    /// 识别功能：text（默认）或 document
+    pub feature: Option<GoogleFeature>,
+    /// 语言提示
+    #[serde(default)]
+    pub language_hints: Vec<String>,
+}
+
+/// 配置文件中的百度文字识别后端设置（`[baidu]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BaiduConfig {
+    /// 接口根地址，默认为 [`DEFAULT_BAIDU_URL`]
+    pub url: Option<String>,
+    /// 应用的 API Key
+    pub api_key: Option<SecretRef>,
+    /// 应用的 Secret Key
+    pub secret_key: Option<SecretRef>,
+    /// 预先获取的 `access_token`，设置后不再用 API Key 换取
+    pub access_token: Option<SecretRef>,
+    /// 识别接口：general_basic（默认）或 accurate
+    pub model: Option<BaiduModel>,
+    /// 识别语言（`language_type`）
+    pub language_type: Option<String>,
+}
+
+/// 配置文件中的OCR识别参数（`[ocr]` 表）
+///
+/// 未设置的参数由服务器使用默认值。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OcrConfig {
+    /// 识别语言（模型配置）
+    pub language: Option<String>,
+    /// 是否启用文字方向纠正
+    pub cls: Option<bool>,
+    /// 图片最长边的限制
+    pub limit_side_len: Option<u32>,
+    /// 排版解析方案
+    pub parser: Option<TbpuParser>,
+    /// 忽略区域
+    pub ignore_area: Option<Vec<IgnoreArea>>,
+}
+
+impl OcrConfig {
+    /// 转换为OCR请求选项
+    pub fn to_options(&self) -> OcrOptions {
+        OcrOptions {
+            language: self.language.clone(),
+            cls: self.cls,
+            limit_side_len: self.limit_side_len,
+            parser: self.parser,
+            ignore_area: self.ignore_area.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// 用OCR请求选项更新配置
+    pub fn update_with_options(&mut self, options: &OcrOptions) {
+        self.language = options.language.clone();
+        self.cls = options.cls;
+        self.limit_side_len = options.limit_side_len;
+        self.parser = options.parser;
+        self.ignore_area = options.ignore_area.clone();
+    }
+}
+
+/// 配置文件中的图片预处理设置（`[preprocess]` 表）
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PreprocessConfig {
+    /// 按顺序执行的步骤，如 `["grayscale", "contrast", "upscale"]`
+    pub steps: Option<Vec<PreprocessStep>>,
+    /// 放大倍数，默认为 2
+    pub upscale_factor: Option<f32>,
+    /// 二值化的邻域边长（像素），默认为图片最长边的1/16
+    pub binarize_window: Option<u32>,
+    /// 上传图片最长边的上限（像素），超过时等比缩小
+    pub max_side: Option<u32>,
+    /// 上传图片的字节数上限（base64编码前），超过时降低JPEG质量或缩小图片
+    pub max_bytes: Option<u64>,
+    /// 重新编码的格式：`png` 或 `jpeg`
+    pub format: Option<OutputFormat>,
+    /// JPEG质量（1~100），默认为85
+    pub jpeg_quality: Option<u8>,
+    /// 分别旋转0°、90°、180°、270°识别，保留平均置信度最高的结果
+    pub auto_rotate: Option<bool>,
+}
+
+impl PreprocessConfig {
+    /// 转换为预处理流程
+    pub fn to_pipeline(&self) -> Pipeline {
+        let mut pipeline = Pipeline::new(self.steps.clone().unwrap_or_default());
+        if let Some(factor) = self.upscale_factor {
+            pipeline = pipeline.upscale_factor(factor);
+        }
+        if let Some(window) = self.binarize_window {
+            pipeline = pipeline.binarize_window(window);
+        }
+        if let Some(max_side) = self.max_side {
+            pipeline = pipeline.max_side(max_side);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            pipeline = pipeline.max_bytes(max_bytes);
+        }
+        if let Some(format) = self.format {
+            pipeline = pipeline.output_format(format);
+        }
+        if let Some(quality) = self.jpeg_quality {
+            pipeline = pipeline.jpeg_quality(quality);
+        }
+        if let Some(enabled) = self.auto_rotate {
+            pipeline = pipeline.auto_rotate(enabled);
+        }
+        pipeline
+    }
 }
 
 impl Config {
@@ -42,13 +493,77 @@ impl Config {
 
     /// 保存配置到文件
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path();
+        self.save_to(&Self::config_path())
+    }
+
+    /// 保存配置到指定文件
+    ///
+    /// 配置中有明文密钥时，文件权限设为 `0600`（仅Unix）。
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(RsOcrError::Io)?;
+        }
         let content =
             toml::to_string_pretty(self).map_err(|e| RsOcrError::Config(e.to_string()))?;
-        fs::write(config_path, content).map_err(RsOcrError::Io)?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        if self.has_plain_secrets() {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path).map_err(RsOcrError::Io)?;
+        // 已存在的文件不会按 `mode` 修改权限
+        #[cfg(unix)]
+        if self.has_plain_secrets() {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .map_err(RsOcrError::Io)?;
+        }
+        file.write_all(content.as_bytes()).map_err(RsOcrError::Io)?;
         Ok(())
     }
 
+    /// 配置中的全部密钥引用
+    fn secrets(&self) -> [&Option<SecretRef>; 7] {
+        [
+            &self.auth.token,
+            &self.auth.password,
+            &self.google.api_key,
+            &self.google.access_token,
+            &self.baidu.api_key,
+            &self.baidu.secret_key,
+            &self.baidu.access_token,
+        ]
+    }
+
+    /// 是否有直接写明文的密钥
+    pub fn has_plain_secrets(&self) -> bool {
+        self.secrets()
+            .into_iter()
+            .any(|secret| matches!(secret, Some(SecretRef::Plain(_))))
+    }
+
+    /// 把明文密钥替换为 `***` 后的副本，用于显示配置
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        for secret in [
+            &mut config.auth.token,
+            &mut config.auth.password,
+            &mut config.google.api_key,
+            &mut config.google.access_token,
+            &mut config.baidu.api_key,
+            &mut config.baidu.secret_key,
+            &mut config.baidu.access_token,
+        ] {
+            if let Some(SecretRef::Plain(value)) = secret {
+                *value = "***".to_string();
+            }
+        }
+        config
+    }
+
     /// 合并配置：命令行参数优先，然后是配置文件，最后是默认值
     ///
     /// # 参数
@@ -66,13 +581,311 @@ impl Config {
             .or_else(|| self.file.clone())
             .ok_or_else(|| RsOcrError::Config("必须提供图片文件路径".to_string()))?;
 
-        let url = args_url
-            .or_else(|| self.url.clone())
-            .unwrap_or_else(|| "http://127.0.0.1:1224/api/ocr".to_string());
+        let url = self.api_url(args_url);
 
         Ok((file, url))
     }
 
+    /// 获取OCR API地址：命令行参数优先，然后是配置文件，最后是默认值
+    pub fn api_url(&self, args_url: Option<String>) -> String {
+        args_url
+            .or_else(|| self.url.clone())
+            .unwrap_or_else(|| DEFAULT_URL.to_string())
+    }
+
+    /// OCR后端名称，未设置时为 [`DEFAULT_BACKEND`]
+    pub fn backend_name(&self) -> &str {
+        self.backend.as_deref().unwrap_or(DEFAULT_BACKEND)
+    }
+
+    /// 根据配置创建OCR客户端构建器
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的URL，优先于配置文件
+    ///
+    /// # 返回
+    /// * `Result<OcrClientBuilder>` - 已设置API地址、超时、请求头、认证、重试策略和默认OCR参数的构建器，
+    ///   认证配置不完整时返回 [`RsOcrError::Config`]
+    pub fn client_builder(&self, args_url: Option<String>) -> Result<OcrClientBuilder> {
+        let mut builder = self
+            .transport_builder(self.api_url(args_url))?
+            .options(self.ocr.to_options());
+        for (name, value) in &self.client.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(auth) = self.auth.to_auth()? {
+            builder = builder.auth(auth);
+        }
+        Ok(builder)
+    }
+
+    /// 下载URL图片使用的HTTP客户端
+    ///
+    /// 使用 `[client]` 中的超时、代理、证书和请求头，不带 `[auth]`。
+    ///
+    /// # 返回
+    /// * `Result<reqwest::blocking::Client>` - 代理、证书或请求头无效时返回 [`RsOcrError::Config`]
+    pub fn download_client(&self) -> Result<reqwest::blocking::Client> {
+        let mut builder = self.transport_builder(DEFAULT_URL.to_string())?;
+        for (name, value) in &self.client.headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.build()?.http().clone())
+    }
+
+    /// 只设置超时、代理、证书和重试策略的构建器
+    ///
+    /// 不带 `[auth]` 和 `client.headers`，供自带认证信息的第三方后端使用，
+    /// 避免把访问自建服务的令牌发给外部接口。
+    fn transport_builder(&self, url: String) -> Result<OcrClientBuilder> {
+        let mut builder = OcrClientBuilder::new(url).retry(
+            self.retry
+                .as_ref()
+                .map_or_else(RetryPolicy::none, RetryConfig::to_policy),
+        );
+        if let Some(breaker) = self.circuit_breaker.to_breaker() {
+            builder = builder.circuit_breaker(breaker);
+        }
+
+        if let Some(secs) = self.client.connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.client.timeout {
+            builder = builder.timeout((secs > 0).then(|| Duration::from_secs(secs)));
+        }
+        if let Some(user_agent) = &self.client.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        self.client.apply_transport(builder)
+    }
+
+    /// 根据 `[http]` 表创建通用HTTP后端
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的URL，优先于 `http.url` 和顶层的 `url`
+    ///
+    /// # 返回
+    /// * `Result<HttpBackend>` - 模板或选择器无效时返回 [`RsOcrError::Config`]
+    pub fn http_backend(&self, args_url: Option<String>) -> Result<HttpBackend> {
+        let client = build_http_client(
+            "http",
+            self.client_builder(args_url.or_else(|| self.http.url.clone()))?,
+        )?;
+        let backend = HttpBackend::new(client, self.http.template.as_deref(), &self.http.response)?;
+        Ok(match self.http.body.unwrap_or_default() {
+            BodyKind::Json => backend,
+            BodyKind::Multipart => backend.multipart(
+                self.http.file_field.as_deref().unwrap_or("file"),
+                self.http.form.clone(),
+            ),
+        })
+    }
+
+    /// 根据 `[paddle]` 表创建PaddleOCR后端
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的URL，优先于 `paddle.url`
+    pub fn paddle_backend(&self, args_url: Option<String>) -> Result<PaddleBackend> {
+        let url = args_url
+            .or_else(|| self.paddle.url.clone())
+            .unwrap_or_else(|| DEFAULT_PADDLE_URL.to_string());
+        let client = build_http_client(
+            "paddle",
+            self.client_builder(Some(url))?
+                .options(OcrOptions::default()),
+        )?;
+        Ok(PaddleBackend::new(client))
+    }
+
+    /// 根据 `[llm]` 表创建视觉大模型后端
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的URL，优先于 `llm.url`
+    ///
+    /// # 返回
+    /// * `Result<LlmBackend>` - 未设置 `llm.model` 时返回 [`RsOcrError::Config`]
+    pub fn llm_backend(&self, args_url: Option<String>) -> Result<LlmBackend> {
+        let model = self
+            .llm
+            .model
+            .clone()
+            .ok_or_else(|| RsOcrError::Config("使用llm后端时必须设置 llm.model".to_string()))?;
+        let url = args_url
+            .or_else(|| self.llm.url.clone())
+            .unwrap_or_else(|| DEFAULT_LLM_URL.to_string());
+        let client = build_http_client(
+            "llm",
+            self.client_builder(Some(url))?
+                .options(OcrOptions::default()),
+        )?;
+
+        let mut backend = LlmBackend::new(client, model).json_boxes(self.llm.json_boxes);
+        if let Some(prompt) = &self.llm.prompt {
+            backend = backend.prompt(prompt);
+        }
+        if let Some(max_tokens) = self.llm.max_tokens {
+            backend = backend.max_tokens(max_tokens);
+        }
+        Ok(backend)
+    }
+
+    /// 根据 `[google]` 表创建 Google Cloud Vision 后端
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的接口根地址，优先于 `google.url`
+    ///
+    /// # 返回
+    /// * `Result<GoogleVisionBackend>` - 未设置认证信息时返回 [`RsOcrError::Config`]
+    pub fn google_backend(&self, args_url: Option<String>) -> Result<GoogleVisionBackend> {
+        let google = &self.google;
+        if google.api_key.is_none() && google.access_token.is_none() {
+            return Err(RsOcrError::Config(
+                "使用google后端时必须设置 google.api_key 或 google.access_token".to_string(),
+            ));
+        }
+        let url = args_url
+            .or_else(|| google.url.clone())
+            .unwrap_or_else(|| DEFAULT_GOOGLE_URL.to_string());
+        let client = build_http_client("google", self.transport_builder(url)?)?;
+
+        let mut backend = GoogleVisionBackend::new(client)
+            .feature(google.feature.unwrap_or_default())
+            .language_hints(google.language_hints.clone());
+        if let Some(key) = &google.api_key {
+            backend = backend.api_key(key.resolve()?);
+        }
+        if let Some(token) = &google.access_token {
+            backend = backend.access_token(token.resolve()?);
+        }
+        Ok(backend)
+    }
+
+    /// 根据 `[baidu]` 表创建百度文字识别后端
+    ///
+    /// # 参数
+    /// * `args_url` - 命令行提供的接口根地址，优先于 `baidu.url`
+    ///
+    /// # 返回
+    /// * `Result<BaiduBackend>` - 未设置认证信息时返回 [`RsOcrError::Config`]
+    pub fn baidu_backend(&self, args_url: Option<String>) -> Result<BaiduBackend> {
+        let baidu = &self.baidu;
+        let url = args_url
+            .or_else(|| baidu.url.clone())
+            .unwrap_or_else(|| DEFAULT_BAIDU_URL.to_string());
+        let client = build_http_client("baidu", self.transport_builder(url)?)?;
+
+        let backend = match (&baidu.access_token, &baidu.api_key, &baidu.secret_key) {
+            (Some(token), _, _) => BaiduBackend::with_token(client, token.resolve()?),
+            (None, Some(api_key), Some(secret_key)) => {
+                BaiduBackend::new(client, api_key.resolve()?, secret_key.resolve()?)
+            }
+            _ => {
+                return Err(RsOcrError::Config(
+                    "使用baidu后端时必须设置 baidu.api_key 和 baidu.secret_key，或 baidu.access_token"
+                        .to_string(),
+                ));
+            }
+        };
+        let backend = backend.model(baidu.model.unwrap_or_default());
+        Ok(match &baidu.language_type {
+            Some(language_type) => backend.language_type(language_type),
+            None => backend,
+        })
+    }
+
+    /// 读取配置项
+    ///
+    /// # 参数
+    /// * `key` - 配置项名称，嵌套项使用 `.` 分隔
+    ///
+    /// # 返回
+    /// * `Result<Option<String>>` - 配置项的值，未设置时返回 `None`
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let table = self.to_table()?;
+        let mut current = &table;
+        let mut parts = key.split('.').peekable();
+
+        while let Some(part) = parts.next() {
+            match current.get(part) {
+                Some(toml::Value::Table(inner)) if parts.peek().is_some() => current = inner,
+                Some(value) if parts.peek().is_none() => {
+                    return Ok(Some(match value {
+                        toml::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    }));
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// 修改配置项
+    ///
+    /// 值会先按TOML字面量解析（如 `true`、`30`、`["a", "b"]`），
+    /// 解析失败或类型不匹配时按字符串处理。
+    ///
+    /// # 参数
+    /// * `key` - 配置项名称，嵌套项使用 `.` 分隔
+    /// * `value` - 新的值
+    ///
+    /// # 返回
+    /// * `Result<()>` - 配置项不存在或值无效时返回错误
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let literal = toml::from_str::<toml::Table>(&format!("v = {}", value))
+            .ok()
+            .and_then(|mut t| t.remove("v"));
+
+        let updated = match literal {
+            Some(v) => self
+                .with_value(key, v)
+                .or_else(|_| self.with_value(key, toml::Value::String(value.to_string())))?,
+            None => self.with_value(key, toml::Value::String(value.to_string()))?,
+        };
+
+        *self = updated;
+        Ok(())
+    }
+
+    /// 以TOML格式输出全部配置
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| RsOcrError::Config(e.to_string()))
+    }
+
+    fn to_table(&self) -> Result<toml::Table> {
+        toml::Table::try_from(self).map_err(|e| RsOcrError::Config(e.to_string()))
+    }
+
+    fn with_value(&self, key: &str, value: toml::Value) -> Result<Self> {
+        let mut table = self.to_table()?;
+        let parts: Vec<&str> = key.split('.').collect();
+        let (last, parents) = parts
+            .split_last()
+            .ok_or_else(|| RsOcrError::Config("配置项名称不能为空".to_string()))?;
+
+        let mut current = &mut table;
+        for part in parents {
+            current = current
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| RsOcrError::Config(format!("配置项不是表: {}", part)))?;
+        }
+        current.insert(last.to_string(), value);
+
+        let updated: Self = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| RsOcrError::Config(format!("{}: {}", key, e)))?;
+
+        // 未知的配置项在反序列化时会被忽略，这里通过重新读取来检测
+        if updated.get(key)?.is_none() {
+            return Err(RsOcrError::Config(format!("未知的配置项: {}", key)));
+        }
+
+        Ok(updated)
+    }
+
     /// 使用命令行参数更新配置
     pub fn update_with_args(&mut self, args_file: Option<String>, args_url: Option<String>) {
         if let Some(file) = args_file {
@@ -84,11 +897,39 @@ impl Config {
     }
 }
 
+/// 构建只能通过HTTP(S)访问的后端使用的客户端
+///
+/// 这些后端直接使用底层的HTTP客户端发送请求，不支持 `unix://` 地址。
+fn build_http_client(backend: &str, builder: OcrClientBuilder) -> Result<OcrClient> {
+    let client = builder.build()?;
+    if is_unix_url(client.url()) {
+        return Err(RsOcrError::Config(format!(
+            "{}后端不支持Unix域套接字地址: {}",
+            backend,
+            client.url()
+        )));
+    }
+    Ok(client)
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             file: None,
-            url: Some("http://127.0.0.1:1224/api/ocr".to_string()),
+            url: Some(DEFAULT_URL.to_string()),
+            backend: None,
+            ocr: OcrConfig::default(),
+            preprocess: PreprocessConfig::default(),
+            client: ClientConfig::default(),
+            auth: AuthConfig::default(),
+            retry: None,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            tesseract: TesseractConfig::default(),
+            http: HttpConfig::default(),
+            paddle: PaddleConfig::default(),
+            llm: LlmConfig::default(),
+            google: GoogleConfig::default(),
+            baidu: BaiduConfig::default(),
         }
     }
 }
@@ -96,6 +937,8 @@ impl Default for Config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::OcrBackend;
+    use crate::test_support::{MockResponse, MockServer, tls_fixture};
     use std::fs;
     use tempfile::TempDir;
 
@@ -191,6 +1034,7 @@ mod tests {
         let config = Config {
             file: Some("test.png".to_string()),
             url: Some("http://test.com/api".to_string()),
+            ..Default::default()
         };
 
         let test_config = TestConfig::new(config, config_dir);
@@ -211,6 +1055,7 @@ mod tests {
         let config = Config {
             file: Some("default.png".to_string()),
             url: Some("http://default.com/api".to_string()),
+            ..Default::default()
         };
 
         // 测试命令行参数优先
@@ -235,6 +1080,7 @@ mod tests {
         let config_without_file = Config {
             file: None,
             url: Some("http://test.com/api".to_string()),
+            ..Default::default()
         };
 
         let result = config_without_file.merge_with_args(None, None);
@@ -248,4 +1094,286 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_and_set() -> Result<()> {
+        let mut config = Config::default();
+        assert_eq!(config.get("url")?, Some(DEFAULT_URL.to_string()));
+        assert_eq!(config.get("file")?, None);
+
+        config.set("file", "test.png")?;
+        assert_eq!(config.file, Some("test.png".to_string()));
+
+        // 数字字面量也应能作为字符串类型的值
+        config.set("file", "123")?;
+        assert_eq!(config.file, Some("123".to_string()));
+
+        let result = config.set("no_such_key", "value");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("未知的配置项"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ocr_section() -> Result<()> {
+        let mut config = Config::default();
+        config.set("ocr.cls", "true")?;
+        config.set("ocr.limit_side_len", "960")?;
+        config.set("ocr.parser", "single_code")?;
+        config.set("ocr.ignore_area", "[[[0, 0], [100, 20]]]")?;
+        assert_eq!(config.get("ocr.parser")?, Some("single_code".to_string()));
+
+        let options = config.ocr.to_options();
+        assert_eq!(options.cls, Some(true));
+        assert_eq!(options.limit_side_len, Some(960));
+        assert_eq!(options.parser, Some(TbpuParser::SingleCode));
+        assert_eq!(options.ignore_area, Some(vec![[[0, 0], [100, 20]]]));
+
+        assert!(config.set("ocr.parser", "no_such_parser").is_err());
+
+        // 没有 [ocr] 和 [client] 表的旧配置文件仍能加载
+        let old: Config = toml::from_str("url = \"http://test.com/api\"").unwrap();
+        assert_eq!(old.ocr, OcrConfig::default());
+        assert_eq!(old.client, ClientConfig::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preprocess_section() -> Result<()> {
+        let mut config = Config::default();
+        assert!(config.preprocess.to_pipeline().is_empty());
+
+        config.set("preprocess.steps", "[\"grayscale\", \"upscale\"]")?;
+        config.set("preprocess.upscale_factor", "3.0")?;
+        let pipeline = config.preprocess.to_pipeline();
+        assert_eq!(
+            pipeline.steps(),
+            [PreprocessStep::Grayscale, PreprocessStep::Upscale]
+        );
+
+        assert!(config.set("preprocess.steps", "[\"blur\"]").is_err());
+
+        // 只设置尺寸限制时也会包装后端
+        let mut config = Config::default();
+        config.set("preprocess.max_side", "4000")?;
+        config.set("preprocess.format", "jpeg")?;
+        assert!(!config.preprocess.to_pipeline().is_empty());
+        assert_eq!(config.preprocess.format, Some(OutputFormat::Jpeg));
+        assert!(config.set("preprocess.format", "webp").is_err());
+
+        let mut config = Config::default();
+        config.set("preprocess.auto_rotate", "true")?;
+        let pipeline = config.preprocess.to_pipeline();
+        assert!(pipeline.is_auto_rotate() && !pipeline.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_section() -> Result<()> {
+        let mut config = Config::default();
+        config.set("client.timeout", "30")?;
+        config.set("client.user_agent", "my-agent")?;
+        config.set("client.headers.X-Team", "ocr")?;
+
+        assert_eq!(config.client.timeout, Some(30));
+        assert_eq!(
+            config.client.headers.get("X-Team"),
+            Some(&"ocr".to_string())
+        );
+        assert_eq!(
+            config.get("client.headers.X-Team")?,
+            Some("ocr".to_string())
+        );
+
+        let client = config.client_builder(None)?.build()?;
+        assert_eq!(client.url(), DEFAULT_URL);
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_sections() -> Result<()> {
+        let config = Config::default();
+        assert!(config.retry.is_none());
+        assert_eq!(
+            config.client_builder(None)?.build()?.retry_policy(),
+            &RetryPolicy::none()
+        );
+        assert!(config.circuit_breaker.to_breaker().is_none());
+
+        let mut config = Config::default();
+        config.set("retry.max_attempts", "5")?;
+        config.set("retry.initial_backoff_ms", "100")?;
+        config.set("retry.status", "[503]")?;
+        config.set("circuit_breaker.failure_threshold", "3")?;
+
+        let policy = config.retry.as_ref().unwrap().to_policy();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(100));
+        assert_eq!(policy.retry_status, vec![503]);
+        assert!(config.circuit_breaker.to_breaker().is_some());
+
+        let client = config.client_builder(None)?.build()?;
+        assert_eq!(client.retry_policy().max_attempts, 5);
+        assert!(client.circuit_breaker().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tls_settings() -> Result<()> {
+        let server = MockServer::start_tls(true, |_| MockResponse::umi_text("tls", 1.0));
+        let mut config = Config::default();
+        config.set("client.ca_certs", &format!("[{:?}]", tls_fixture("ca.pem")))?;
+        config.set(
+            "client.client_cert",
+            &tls_fixture("client.pem").to_string_lossy(),
+        )?;
+        assert!(matches!(
+            config.client_builder(None),
+            Err(RsOcrError::Config(_))
+        ));
+
+        config.set(
+            "client.client_key",
+            &tls_fixture("client.key").to_string_lossy(),
+        )?;
+        let client = config
+            .client_builder(Some(format!("{}/api/ocr", server.url())))?
+            .build()?;
+        assert_eq!(client.recognize("aGVsbG8=")?.text(), "tls");
+
+        config.set("client.ca_certs", "[\"/nonexistent/ca.pem\"]")?;
+        let err = config.client_builder(None).unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/ca.pem"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_auth_section() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [auth]
+            type = "basic"
+            username = "ocr"
+            password = "env:RSOCR_TEST_PASSWORD"
+            "#,
+        )
+        .map_err(|e| RsOcrError::Config(e.to_string()))?;
+        assert_eq!(
+            config.auth.to_auth()?,
+            Some(Auth::Basic {
+                username: "ocr".to_string(),
+                password: SecretRef::Env("RSOCR_TEST_PASSWORD".to_string()),
+            })
+        );
+        // 引用按原样保存
+        assert!(
+            config
+                .to_toml()?
+                .contains("password = \"env:RSOCR_TEST_PASSWORD\"")
+        );
+        // 环境变量未设置时无法构建客户端
+        assert!(matches!(
+            config.client_builder(None)?.build(),
+            Err(RsOcrError::Config(_))
+        ));
+
+        let mut config = Config::default();
+        config.set("auth.type", "api_key")?;
+        assert!(matches!(
+            config.client_builder(None),
+            Err(RsOcrError::Config(_))
+        ));
+        config.set("auth.token", "plain-key")?;
+        assert_eq!(
+            config.auth.to_auth()?,
+            Some(Auth::ApiKey {
+                header: "X-API-Key".to_string(),
+                key: SecretRef::Plain("plain-key".to_string()),
+            })
+        );
+        assert!(!format!("{:?}", config).contains("plain-key"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unix_url_backends() -> Result<()> {
+        let mut config = Config::default();
+        config.set("llm.model", "gpt-4o")?;
+        let unix = Some("unix:///run/ocr.sock".to_string());
+        assert!(matches!(
+            config.http_backend(unix.clone()),
+            Err(RsOcrError::Config(ref m)) if m.contains("http后端")
+        ));
+        assert!(matches!(
+            config.paddle_backend(unix.clone()),
+            Err(RsOcrError::Config(_))
+        ));
+        assert!(matches!(
+            config.llm_backend(unix),
+            Err(RsOcrError::Config(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plain_secrets() -> Result<()> {
+        let mut config = Config::default();
+        config.set("auth.token", "env:OCR_TOKEN")?;
+        assert!(!config.has_plain_secrets());
+        config.set("baidu.secret_key", "plain-secret")?;
+        assert!(config.has_plain_secrets());
+
+        // 显示配置时隐藏明文，引用原样显示
+        let shown = config.redacted();
+        assert_eq!(shown.get("baidu.secret_key")?, Some("***".to_string()));
+        assert_eq!(shown.get("auth.token")?, Some("env:OCR_TOKEN".to_string()));
+        assert!(!shown.to_toml()?.contains("plain-secret"));
+
+        // 保存时保留明文，文件只允许所有者读写
+        let dir = TempDir::new()?;
+        let path = dir.path().join("rsOCR.toml");
+        fs::write(&path, "")?;
+        config.save_to(&path)?;
+        assert!(fs::read_to_string(&path)?.contains("plain-secret"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_third_party_credentials() -> Result<()> {
+        let server =
+            MockServer::start(|_| MockResponse::json(serde_json::json!({"responses": [{}]})));
+        let mut config = Config::default();
+        config.set("auth.type", "api_key")?;
+        config.set("auth.token", "umi-token")?;
+        config.set("client.headers.X-Team", "ocr")?;
+        config.set("google.url", &server.url())?;
+        config.set("google.api_key", "google-key")?;
+
+        // `[auth]` 和 `client.headers` 只发给自建服务
+        let backend = config.google_backend(None)?;
+        assert!(!format!("{:?}", backend).contains("google-key"));
+        backend.recognize_bytes(b"img", &OcrOptions::default())?;
+        let request = &server.requests()[0];
+        assert_eq!(request.header("x-goog-api-key"), Some("google-key"));
+        assert_eq!(request.header("x-api-key"), None);
+        assert_eq!(request.header("x-team"), None);
+
+        config.set("baidu.url", "http://127.0.0.1:1")?;
+        config.set("baidu.api_key", "baidu-ak")?;
+        config.set("baidu.secret_key", "baidu-sk")?;
+        let backend = config.baidu_backend(None)?;
+        assert!(!format!("{:?}", backend).contains("baidu-sk"));
+        // 错误信息中不包含带密钥的URL
+        let err = backend.health().unwrap_err();
+        assert!(matches!(err, RsOcrError::Http(_)));
+        assert!(!err.to_string().contains("baidu-sk"));
+        Ok(())
+    }
 }
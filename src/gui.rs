@@ -1,10 +1,254 @@
 slint::include_modules!();
 
-use crate::models::RsOcrError;
+use crate::backend::create_backend;
+use crate::capabilities::{self, OptionChoice};
+use crate::config::Config;
+use crate::models::{OcrOptions, Result, TbpuParser, parse_ignore_area};
 use crate::ocr_utils;
+use crate::preprocess::PreprocessStep;
 use arboard::Clipboard;
 use rfd::FileDialog;
-use slint::SharedString;
+use slint::{Model, ModelRc, SharedString, VecModel};
+
+/// “最长边”下拉框中表示使用服务器默认值的选项
+const DEFAULT_CHOICE: &str = "默认";
+
+/// 排版解析方案在下拉框中的显示名称
+fn parser_label(parser: TbpuParser) -> &'static str {
+    match parser {
+        TbpuParser::MultiPara => "多栏-按自然段换行",
+        TbpuParser::MultiLine => "多栏-总是换行",
+        TbpuParser::MultiNone => "多栏-无换行",
+        TbpuParser::SinglePara => "单栏-按自然段换行",
+        TbpuParser::SingleLine => "单栏-总是换行",
+        TbpuParser::SingleNone => "单栏-无换行",
+        TbpuParser::SingleCode => "单栏-保留缩进",
+        TbpuParser::VerticalRl => "竖排-从右到左",
+        TbpuParser::None => "不做处理",
+    }
+}
+
+/// “最长边”下拉框的内置选项，服务器提供可选值时会被替换
+const LIMIT_SIDE_LENS: [&str; 4] = ["960", "2880", "4320", "999999"];
+
+fn string_model(items: Vec<String>) -> ModelRc<SharedString> {
+    ModelRc::new(VecModel::from(
+        items
+            .into_iter()
+            .map(SharedString::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// 用可选值填充下拉框，并尽量保持当前选择
+///
+/// 每个下拉框的第一项表示使用服务器默认值。
+fn apply_choices(
+    ui: &rsOCR,
+    languages: &[OptionChoice],
+    limits: &[String],
+    parsers: &[TbpuParser],
+    current: &OcrOptions,
+) {
+    let mut labels = vec!["服务器默认".to_string()];
+    let mut values = vec![String::new()];
+    for choice in languages {
+        labels.push(choice.label.clone());
+        values.push(choice.value.clone());
+    }
+    let language_index = current
+        .language
+        .as_ref()
+        .and_then(|lang| values.iter().position(|v| v == lang))
+        .unwrap_or(0);
+    ui.set_language_labels(string_model(labels));
+    ui.set_language_values(string_model(values));
+    ui.set_language_index(language_index as i32);
+
+    let mut limit_options = vec![DEFAULT_CHOICE.to_string()];
+    limit_options.extend(limits.iter().cloned());
+    ui.set_limit_options(string_model(limit_options));
+    ui.set_limit_side_len(SharedString::from(
+        current
+            .limit_side_len
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| DEFAULT_CHOICE.to_string()),
+    ));
+
+    let mut labels = vec![DEFAULT_CHOICE.to_string()];
+    let mut values = vec![String::new()];
+    for parser in parsers {
+        labels.push(parser_label(*parser).to_string());
+        values.push(parser.as_str().to_string());
+    }
+    let parser_index = current
+        .parser
+        .and_then(|p| parsers.iter().position(|q| *q == p))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    ui.set_parser_labels(string_model(labels));
+    ui.set_parser_values(string_model(values));
+    ui.set_parser_index(parser_index as i32);
+}
+
+/// 所选后端实际使用的地址，用于初始化界面上的API地址
+fn backend_endpoint(config: &Config) -> String {
+    create_backend(config, None)
+        .map(|backend| backend.endpoint().to_string())
+        .unwrap_or_else(|_| config.api_url(None))
+}
+
+/// 界面上的API地址是否覆盖配置文件
+///
+/// Umi-OCR后端总是使用界面上的地址；其他后端的地址来自各自的配置表，
+/// 只有用户修改了界面上的地址时才覆盖。
+fn url_override(config: &Config, api_url: String) -> Option<String> {
+    let is_umi = matches!(config.backend_name(), "umi" | "umi-ocr");
+    (is_umi || api_url != backend_endpoint(config)).then_some(api_url)
+}
+
+/// 用配置初始化界面上的API地址和OCR参数
+fn load_options_into_ui(ui: &rsOCR, config: &Config) {
+    ui.set_api_url(SharedString::from(backend_endpoint(config)));
+
+    let options = config.ocr.to_options();
+
+    // 在查询到服务器支持的语言之前，只提供配置文件中的语言
+    let languages: Vec<OptionChoice> = options
+        .language
+        .iter()
+        .map(|lang| OptionChoice {
+            value: lang.clone(),
+            label: lang.clone(),
+        })
+        .collect();
+    let mut limits: Vec<String> = LIMIT_SIDE_LENS.iter().map(|s| s.to_string()).collect();
+    let configured_limit = options.limit_side_len.map(|n| n.to_string());
+    if let Some(limit) = configured_limit.filter(|l| !limits.contains(l)) {
+        limits.push(limit);
+    }
+    apply_choices(ui, &languages, &limits, &TbpuParser::ALL, &options);
+
+    ui.set_ocr_cls(options.cls.unwrap_or(false));
+
+    let areas = options
+        .ignore_area
+        .unwrap_or_default()
+        .iter()
+        .map(|[[x0, y0], [x1, y1]]| format!("{},{},{},{}", x0, y0, x1, y1))
+        .collect::<Vec<_>>()
+        .join(";");
+    ui.set_ignore_area(SharedString::from(areas));
+
+    for step in config.preprocess.steps.iter().flatten() {
+        set_preprocess_step(ui, *step, true);
+    }
+}
+
+/// 设置预处理步骤对应的复选框
+fn set_preprocess_step(ui: &rsOCR, step: PreprocessStep, checked: bool) {
+    match step {
+        PreprocessStep::Grayscale => ui.set_pre_grayscale(checked),
+        PreprocessStep::Contrast => ui.set_pre_contrast(checked),
+        PreprocessStep::Binarize => ui.set_pre_binarize(checked),
+        PreprocessStep::Denoise => ui.set_pre_denoise(checked),
+        PreprocessStep::Sharpen => ui.set_pre_sharpen(checked),
+        PreprocessStep::Upscale => ui.set_pre_upscale(checked),
+    }
+}
+
+/// 从界面读取勾选的预处理步骤，按 [`PreprocessStep::ALL`] 的推荐顺序排列
+fn preprocess_from_ui(ui: &rsOCR) -> Vec<PreprocessStep> {
+    PreprocessStep::ALL
+        .into_iter()
+        .filter(|step| match step {
+            PreprocessStep::Grayscale => ui.get_pre_grayscale(),
+            PreprocessStep::Contrast => ui.get_pre_contrast(),
+            PreprocessStep::Binarize => ui.get_pre_binarize(),
+            PreprocessStep::Denoise => ui.get_pre_denoise(),
+            PreprocessStep::Sharpen => ui.get_pre_sharpen(),
+            PreprocessStep::Upscale => ui.get_pre_upscale(),
+        })
+        .collect()
+}
+
+/// 从界面读取OCR参数
+fn options_from_ui(ui: &rsOCR) -> Result<OcrOptions> {
+    let selected = |values: ModelRc<SharedString>, index: i32| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| values.row_data(i))
+            .map(|v| v.to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let language = selected(ui.get_language_values(), ui.get_language_index());
+    let parser = selected(ui.get_parser_values(), ui.get_parser_index());
+    let limit_side_len = ui.get_limit_side_len().to_string();
+
+    let ignore_area = ui
+        .get_ignore_area()
+        .split(';')
+        .map(str::trim)
+        .filter(|area| !area.is_empty())
+        .map(parse_ignore_area)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(OcrOptions {
+        language,
+        cls: Some(ui.get_ocr_cls()),
+        limit_side_len: limit_side_len.parse().ok(),
+        parser: parser.as_deref().and_then(TbpuParser::from_name),
+        ignore_area: (!ignore_area.is_empty()).then_some(ignore_area),
+        ..Default::default()
+    })
+}
+
+/// 在后台查询服务器支持的参数，并更新下拉框
+fn refresh_options(ui: &rsOCR) {
+    let api_url = ui.get_api_url().to_string();
+    let ui_weak = ui.as_weak();
+
+    std::thread::spawn(move || {
+        capabilities::invalidate_capabilities(&api_url);
+        let config = Config::load();
+        let result = create_backend(&config, url_override(&config, api_url))
+            .and_then(|backend| backend.capabilities());
+
+        slint::invoke_from_event_loop(move || {
+            let ui = ui_weak.unwrap();
+            match result {
+                Ok(caps) => {
+                    let current = options_from_ui(&ui).unwrap_or_default();
+                    let limits: Vec<String> = if caps.limit_side_lens.is_empty() {
+                        LIMIT_SIDE_LENS.iter().map(|s| s.to_string()).collect()
+                    } else {
+                        caps.limit_side_lens
+                            .iter()
+                            .map(|c| c.value.clone())
+                            .collect()
+                    };
+                    let parsers = if caps.parsers.is_empty() {
+                        TbpuParser::ALL.to_vec()
+                    } else {
+                        caps.supported_parsers()
+                    };
+                    apply_choices(&ui, &caps.languages, &limits, &parsers, &current);
+                    log::info!("已获取服务器参数: {} 种语言", caps.languages.len());
+                    ui.set_status_message(SharedString::from(format!(
+                        "已获取服务器参数: {} 种语言",
+                        caps.languages.len()
+                    )));
+                }
+                Err(e) => {
+                    log::warn!("无法获取服务器参数: {}", e);
+                    ui.set_status_message(SharedString::from(format!("无法获取服务器参数: {}", e)));
+                }
+            }
+        })
+        .unwrap();
+    });
+}
 
 /// 运行OCR GUI应用程序
 ///
@@ -13,6 +257,8 @@ use slint::SharedString;
 /// - 执行OCR识别
 /// - 显示识别结果
 /// - 复制结果到剪贴板
+/// - 设置OCR参数（可选值从服务器查询）
+/// - 选择上传前的图片预处理步骤
 ///
 /// # 示例
 ///
@@ -23,6 +269,16 @@ use slint::SharedString;
 /// ```
 pub fn run() {
     let ui = rsOCR::new().expect("component rsOCR new failed!");
+    load_options_into_ui(&ui, &Config::load());
+    refresh_options(&ui);
+
+    // 刷新服务器参数回调
+    let ui_weak0 = ui.as_weak();
+    ui.on_refresh_options(move || {
+        let ui = ui_weak0.unwrap();
+        ui.set_status_message(SharedString::from("正在获取服务器参数..."));
+        refresh_options(&ui);
+    });
 
     // 选择图片回调
     let ui_weak1 = ui.as_weak();
@@ -30,20 +286,21 @@ pub fn run() {
         let ui = ui_weak1.unwrap();
 
         if let Some(path) = FileDialog::new()
-            .add_filter("图片文件", &["png", "jpg", "jpeg", "bmp", "gif"])
+            .add_filter("图片文件", ocr_utils::IMAGE_EXTENSIONS)
             .pick_file()
         {
             let path_str = path.to_string_lossy().to_string();
             ui.set_selected_image_path(SharedString::from(&path_str));
             ui.set_status_message(SharedString::from(format!("已选择图片: {}", path_str)));
 
-            // 加载图片并设置到UI
+            // 加载图片并设置到UI，TIFF等格式无法预览，但识别前会转换为PNG
             if let Ok(image) = slint::Image::load_from_path(&path) {
                 ui.set_selected_image(image);
             } else {
-                let error_msg = format!("无法加载图片: {}", path_str);
-                log::error!("{}", error_msg);
-                ui.set_status_message(SharedString::from(format!("错误: {}", error_msg)));
+                let error_msg = format!("无法预览图片，仍可执行OCR: {}", path_str);
+                log::warn!("{}", error_msg);
+                ui.set_selected_image(slint::Image::default());
+                ui.set_status_message(SharedString::from(error_msg));
             }
         }
     });
@@ -62,6 +319,17 @@ pub fn run() {
             return;
         }
 
+        let options = match options_from_ui(&ui) {
+            Ok(options) => options,
+            Err(e) => {
+                log::error!("{}", e);
+                ui.set_status_message(SharedString::from(format!("错误: {}", e)));
+                return;
+            }
+        };
+
+        let steps = preprocess_from_ui(&ui);
+
         ui.set_processing(true);
         ui.set_status_message(SharedString::from("正在处理图片..."));
         log::info!("开始处理图片: {}", image_path);
@@ -69,36 +337,37 @@ pub fn run() {
         // 在后台线程执行OCR处理
         let ui_weak = ui.as_weak();
         std::thread::spawn(move || {
-            // 将图片转换为base64
-            let result = match ocr_utils::image_to_base64(&image_path) {
-                Ok(base64_code) => {
-                    // 调用OCR API
-                    match ocr_utils::call_ocr_api(&base64_code, &api_url) {
-                        Ok(ocr_response) => {
-                            if let Some(data) = ocr_response.data {
-                                Ok(data)
-                            } else {
-                                Err(RsOcrError::OcrApi("OCR API返回空结果".to_string()))
-                            }
-                        }
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-            };
+            let mut config = Config::load();
+            config.preprocess.steps = Some(steps);
+            let result =
+                create_backend(&config, url_override(&config, api_url)).and_then(|backend| {
+                    backend.validate_options(&options)?;
+                    backend.recognize_path(&image_path, &options)
+                });
 
             // 在主线程中更新UI
             let ui_weak_clone = ui_weak.clone();
             slint::invoke_from_event_loop(move || {
                 let ui = ui_weak_clone.unwrap();
                 match result {
-                    Ok(data) => {
-                        let data_len = data.len();
-                        log::info!("OCR处理成功，识别到 {} 个字符", data_len);
+                    Ok(ocr_result) if ocr_result.is_empty() => {
+                        log::info!("OCR处理完成，图片中没有文字");
+                        ui.set_ocr_result(SharedString::new());
+                        ui.set_status_message(SharedString::from("OCR完成，图片中没有识别到文字"));
+                    }
+                    Ok(ocr_result) => {
+                        let data = ocr_result.text();
+                        let data_len = data.chars().count();
+                        let block_count = ocr_result.blocks.len();
+                        log::info!(
+                            "OCR处理成功，识别到 {} 个文本块，{} 个字符",
+                            block_count,
+                            data_len
+                        );
                         ui.set_ocr_result(SharedString::from(data));
                         ui.set_status_message(SharedString::from(format!(
-                            "OCR完成，识别到 {} 个字符",
-                            data_len
+                            "OCR完成，识别到 {} 个文本块，{} 个字符",
+                            block_count, data_len
                         )));
                     }
                     Err(e) => {
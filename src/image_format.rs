@@ -0,0 +1,355 @@
+//! 按文件头识别图片格式
+//!
+//! 扩展名不可靠，标准输入和URL也没有扩展名。上传前按文件头（magic bytes）判断实际格式：
+//! 不是图片时返回 [`RsOcrError::ImageProcessing`]；TIFF、WebP、PNM、ICO、TGA
+//! 等OCR服务通常不支持的格式转换为PNG。手机照片的EXIF方向由 [`orientation`] 读取。
+
+use crate::models::{Result, RsOcrError};
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageFormat, ImageReader};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek};
+use std::path::Path;
+
+/// 判断格式时读取的文件头长度
+const HEADER_LEN: usize = 16;
+
+/// TGA 2.0 文件末尾的签名
+const TGA_FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+/// 图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageKind {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    Tiff,
+    WebP,
+    /// PBM/PGM/PPM/PAM
+    Pnm,
+    Ico,
+    Tga,
+}
+
+impl ImageKind {
+    /// 所有可识别的格式
+    pub const ALL: [ImageKind; 9] = [
+        ImageKind::Png,
+        ImageKind::Jpeg,
+        ImageKind::Gif,
+        ImageKind::Bmp,
+        ImageKind::Tiff,
+        ImageKind::WebP,
+        ImageKind::Pnm,
+        ImageKind::Ico,
+        ImageKind::Tga,
+    ];
+
+    /// 格式名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageKind::Png => "PNG",
+            ImageKind::Jpeg => "JPEG",
+            ImageKind::Gif => "GIF",
+            ImageKind::Bmp => "BMP",
+            ImageKind::Tiff => "TIFF",
+            ImageKind::WebP => "WebP",
+            ImageKind::Pnm => "PNM",
+            ImageKind::Ico => "ICO",
+            ImageKind::Tga => "TGA",
+        }
+    }
+
+    /// MIME类型
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageKind::Png => "image/png",
+            ImageKind::Jpeg => "image/jpeg",
+            ImageKind::Gif => "image/gif",
+            ImageKind::Bmp => "image/bmp",
+            ImageKind::Tiff => "image/tiff",
+            ImageKind::WebP => "image/webp",
+            ImageKind::Pnm => "image/x-portable-anymap",
+            ImageKind::Ico => "image/x-icon",
+            ImageKind::Tga => "image/x-tga",
+        }
+    }
+
+    /// OCR服务是否普遍支持，不支持的格式上传前转换为PNG
+    pub fn is_widely_supported(&self) -> bool {
+        matches!(
+            self,
+            ImageKind::Png | ImageKind::Jpeg | ImageKind::Gif | ImageKind::Bmp
+        )
+    }
+
+    /// 是否可能包含多页（多页TIFF、GIF动画），见 [`crate::pages`]
+    pub fn can_have_pages(&self) -> bool {
+        matches!(self, ImageKind::Tiff | ImageKind::Gif)
+    }
+
+    /// 对应的 `image` 库格式，用于解码
+    pub fn image_format(&self) -> ImageFormat {
+        match self {
+            ImageKind::Png => ImageFormat::Png,
+            ImageKind::Jpeg => ImageFormat::Jpeg,
+            ImageKind::Gif => ImageFormat::Gif,
+            ImageKind::Bmp => ImageFormat::Bmp,
+            ImageKind::Tiff => ImageFormat::Tiff,
+            ImageKind::WebP => ImageFormat::WebP,
+            ImageKind::Pnm => ImageFormat::Pnm,
+            ImageKind::Ico => ImageFormat::Ico,
+            ImageKind::Tga => ImageFormat::Tga,
+        }
+    }
+}
+
+/// 按文件头判断图片格式
+///
+/// TGA没有固定的文件头，先查找 TGA 2.0 的文件尾签名，再检查文件头各字段是否合理。
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::image_format::{ImageKind, sniff};
+///
+/// assert_eq!(sniff(b"\x89PNG\r\n\x1a\n..."), Some(ImageKind::Png));
+/// assert_eq!(sniff(b"II*\0..."), Some(ImageKind::Tiff));
+/// assert_eq!(sniff(b"<!DOCTYPE html>"), None);
+/// ```
+pub fn sniff(data: &[u8]) -> Option<ImageKind> {
+    let kind = match data {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => ImageKind::Png,
+        [0xFF, 0xD8, 0xFF, ..] => ImageKind::Jpeg,
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => ImageKind::Gif,
+        [b'B', b'M', ..] => ImageKind::Bmp,
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => ImageKind::Tiff,
+        _ if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") => ImageKind::WebP,
+        [b'P', b'1'..=b'7', next, ..] if next.is_ascii_whitespace() => ImageKind::Pnm,
+        [0x00, 0x00, 0x01, 0x00, count_lo, count_hi, ..] if (*count_lo, *count_hi) != (0, 0) => {
+            ImageKind::Ico
+        }
+        _ if is_tga(data) => ImageKind::Tga,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// 是否像TGA文件
+fn is_tga(data: &[u8]) -> bool {
+    if data.ends_with(TGA_FOOTER) {
+        return true;
+    }
+    let Some(header) = data.get(..18) else {
+        return false;
+    };
+    let width = u16::from_le_bytes([header[12], header[13]]);
+    let height = u16::from_le_bytes([header[14], header[15]]);
+    matches!(header[1], 0 | 1)
+        && matches!(header[2], 1 | 2 | 3 | 9 | 10 | 11)
+        && matches!(header[16], 8 | 15 | 16 | 24 | 32)
+        && width > 0
+        && height > 0
+}
+
+/// 判断图片格式，不是图片时返回错误
+///
+/// # 返回
+/// * `Result<ImageKind>` - 数据为空或无法识别格式时返回 [`RsOcrError::ImageProcessing`]
+pub fn detect(data: &[u8]) -> Result<ImageKind> {
+    if data.is_empty() {
+        return Err(RsOcrError::ImageProcessing("图片为空".to_string()));
+    }
+    sniff(data).ok_or_else(|| {
+        let names: Vec<&str> = ImageKind::ALL.iter().map(ImageKind::name).collect();
+        let header: Vec<String> = data.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+        RsOcrError::ImageProcessing(format!(
+            "不是支持的图片格式（文件头 {}，支持 {}）",
+            header.join(" "),
+            names.join("、")
+        ))
+    })
+}
+
+/// 读取文件头判断图片格式
+///
+/// 只读取开头几个字节，因此识别不了TGA，需要时读取整个文件后调用 [`sniff`]。
+///
+/// # 返回
+/// * `Result<Option<ImageKind>>` - 文件无法读取时返回 [`RsOcrError::Io`]
+pub fn sniff_file(path: impl AsRef<Path>) -> Result<Option<ImageKind>> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    File::open(path)
+        .and_then(|file| file.take(HEADER_LEN as u64).read_to_end(&mut header))
+        .map_err(RsOcrError::Io)?;
+    Ok(sniff(&header))
+}
+
+/// 读取图片的EXIF方向
+///
+/// 只有JPEG、PNG、TIFF和WebP可能带有EXIF方向；没有方向信息或无法读取时返回
+/// [`Orientation::NoTransforms`]，解码错误留给之后的解码步骤报告。
+///
+/// # 参数
+/// * `reader` - 图片数据
+/// * `kind` - 图片格式
+pub fn orientation(reader: impl BufRead + Seek, kind: ImageKind) -> Orientation {
+    ImageReader::with_format(reader, kind.image_format())
+        .into_decoder()
+        .and_then(|mut decoder| decoder.orientation())
+        .unwrap_or(Orientation::NoTransforms)
+}
+
+/// 读取图片文件的EXIF方向，见 [`orientation`]
+///
+/// # 返回
+/// * `Result<Orientation>` - 文件无法读取时返回 [`RsOcrError::Io`]
+pub fn orientation_file(path: impl AsRef<Path>) -> Result<Orientation> {
+    let path = path.as_ref();
+    let Some(kind) = sniff_file(path)? else {
+        return Ok(Orientation::NoTransforms);
+    };
+    let file = File::open(path).map_err(RsOcrError::Io)?;
+    Ok(orientation(BufReader::new(file), kind))
+}
+
+/// 检查图片格式，OCR服务通常不支持的格式转换为PNG
+///
+/// # 参数
+/// * `data` - 图片的原始字节
+///
+/// # 返回
+/// * `Result<Cow<[u8]>>` - 普遍支持的格式原样返回；不是图片或无法转换时返回
+///   [`RsOcrError::ImageProcessing`]
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::image_format::normalize;
+///
+/// // 1×1 的灰度PGM图片转换为PNG
+/// let png = normalize(b"P5 1 1 255\n\x80").unwrap();
+/// assert!(png.starts_with(b"\x89PNG"));
+/// assert!(normalize(b"not an image").is_err());
+/// ```
+pub fn normalize(data: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let kind = detect(data)?;
+    if kind.is_widely_supported() {
+        return Ok(Cow::Borrowed(data));
+    }
+
+    let error = |e: image::ImageError| {
+        RsOcrError::ImageProcessing(format!("无法转换{}图片: {}", kind.name(), e))
+    };
+    let image = image::load_from_memory_with_format(data, kind.image_format()).map_err(error)?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png).map_err(error)?;
+    Ok(Cow::Owned(png.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, GrayImage, Luma};
+
+    fn encode(format: ImageFormat) -> Vec<u8> {
+        let image = DynamicImage::ImageLuma8(GrayImage::from_fn(6, 4, |x, _| Luma([x as u8 * 40])));
+        // ICO中的PNG必须是RGBA格式
+        let image = match format {
+            ImageFormat::Ico => DynamicImage::ImageRgba8(image.to_rgba8()),
+            _ => image,
+        };
+        let mut data = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff(b"\xFF\xD8\xFF\xE0"), Some(ImageKind::Jpeg));
+        assert_eq!(sniff(b"GIF89a..."), Some(ImageKind::Gif));
+        assert_eq!(sniff(b"BM\0\0"), Some(ImageKind::Bmp));
+        assert_eq!(sniff(b"MM\0*\0\0\0\x08"), Some(ImageKind::Tiff));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(ImageKind::WebP));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WAVEfmt "), None);
+        assert_eq!(sniff(b"P6\n2 2\n255\n"), Some(ImageKind::Pnm));
+        assert_eq!(sniff(b"P6x"), None);
+        assert_eq!(sniff(b"\0\0\x01\0\x01\0"), Some(ImageKind::Ico));
+        assert_eq!(sniff(&encode(ImageFormat::Tga)), Some(ImageKind::Tga));
+        assert_eq!(sniff(b"hello, this is not an image"), None);
+        assert_eq!(sniff(b""), None);
+
+        for format in [
+            ImageFormat::Png,
+            ImageFormat::Jpeg,
+            ImageFormat::Bmp,
+            ImageFormat::Tiff,
+            ImageFormat::Pnm,
+            ImageFormat::Ico,
+        ] {
+            let kind = sniff(&encode(format)).unwrap();
+            assert_eq!(kind.image_format(), format);
+        }
+    }
+
+    #[test]
+    fn test_orientation() -> Result<()> {
+        let png = encode(ImageFormat::Png);
+        let kind = ImageKind::Png;
+        assert_eq!(
+            orientation(std::io::Cursor::new(&png), kind),
+            Orientation::NoTransforms
+        );
+        assert_eq!(
+            orientation(std::io::Cursor::new(b"\x89PNG broken"), kind),
+            Orientation::NoTransforms
+        );
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(
+            &path,
+            crate::test_support::exif_jpeg(6, 4, Orientation::Rotate90),
+        )?;
+        assert_eq!(orientation_file(&path)?, Orientation::Rotate90);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize() -> Result<()> {
+        let png = encode(ImageFormat::Png);
+        assert!(matches!(normalize(&png)?, Cow::Borrowed(_)));
+
+        for format in [
+            ImageFormat::Tiff,
+            ImageFormat::Pnm,
+            ImageFormat::Ico,
+            ImageFormat::Tga,
+        ] {
+            let data = encode(format);
+            let converted = normalize(&data)?;
+            assert_eq!(sniff(&converted), Some(ImageKind::Png), "{:?}", format);
+            let image = image::load_from_memory(&converted).unwrap();
+            assert_eq!((image.width(), image.height()), (6, 4));
+        }
+
+        let err = normalize(b"<html></html>").unwrap_err();
+        assert!(
+            matches!(err, RsOcrError::ImageProcessing(ref m) if m.contains("3c 68 74 6d")),
+            "{}",
+            err
+        );
+        assert!(matches!(
+            normalize(b""),
+            Err(RsOcrError::ImageProcessing(_))
+        ));
+        // 文件头正确但内容损坏
+        assert!(matches!(
+            normalize(b"II*\0garbage"),
+            Err(RsOcrError::ImageProcessing(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! 图片来源
+//!
+//! 除了本地文件，还可以从字节、任意 [`Read`]、标准输入、`http(s)://` URL
+//! 和 `data:image/...;base64,` URI 读取图片。命令行中的 `-` 表示标准输入：
+//!
+//! ```bash
+//! grim - | rsocr ocr -
+//! curl -s https://example.com/scan.png | rsocr ocr -
+//! rsocr ocr https://example.com/scan.png
+//! ```
+
+use crate::client::shared_http_client;
+use crate::models::{Result, RsOcrError};
+use base64::{Engine as _, engine::general_purpose};
+use reqwest::blocking::Client;
+use std::fmt;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// 表示标准输入的文件名
+pub const STDIN: &str = "-";
+
+/// 下载图片的默认大小上限（100MB）
+pub const DEFAULT_MAX_DOWNLOAD_SIZE: u64 = 100 * 1024 * 1024;
+
+/// 图片来源
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::input::ImageSource;
+///
+/// assert_eq!(ImageSource::parse("-"), ImageSource::Stdin);
+/// assert!(matches!(ImageSource::parse("https://example.com/a.png"), ImageSource::Url(_)));
+/// assert!(matches!(ImageSource::parse("scan.png"), ImageSource::Path(_)));
+///
+/// let image = ImageSource::parse("data:image/png;base64,aGVsbG8=").read().unwrap();
+/// assert_eq!(image, b"hello");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageSource {
+    /// 本地文件
+    Path(PathBuf),
+    /// 标准输入
+    Stdin,
+    /// `http://` 或 `https://` URL
+    Url(String),
+    /// `data:image/...;base64,...` URI
+    DataUri(String),
+}
+
+impl ImageSource {
+    /// 按字符串的形式判断来源，其他字符串都视为文件路径
+    pub fn parse(source: &str) -> Self {
+        if source == STDIN {
+            Self::Stdin
+        } else if is_url(source) {
+            Self::Url(source.to_string())
+        } else if source.starts_with("data:") {
+            Self::DataUri(source.to_string())
+        } else {
+            Self::Path(PathBuf::from(source))
+        }
+    }
+
+    /// 读取图片，URL按 [`DEFAULT_MAX_DOWNLOAD_SIZE`] 限制大小
+    ///
+    /// # 返回
+    /// * `Result<Vec<u8>>` - 图片的原始字节，读取失败或图片为空时返回错误
+    pub fn read(&self) -> Result<Vec<u8>> {
+        self.read_with(shared_http_client())
+    }
+
+    /// 读取图片，URL使用指定的HTTP客户端下载（如 [`Config::download_client`]）
+    ///
+    /// [`Config::download_client`]: crate::Config::download_client
+    pub fn read_with(&self, client: &Client) -> Result<Vec<u8>> {
+        let image = match self {
+            Self::Path(path) => std::fs::read(path).map_err(RsOcrError::Io)?,
+            Self::Stdin => read_image(std::io::stdin().lock())?,
+            Self::Url(url) => download_image_with(client, url, DEFAULT_MAX_DOWNLOAD_SIZE)?,
+            Self::DataUri(uri) => decode_data_uri(uri)?,
+        };
+        if image.is_empty() {
+            return Err(RsOcrError::ImageProcessing(format!("图片为空: {}", self)));
+        }
+        Ok(image)
+    }
+}
+
+impl fmt::Display for ImageSource {
+    /// 用于提示信息，data URI只显示媒体类型
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Stdin => f.write_str("标准输入"),
+            Self::Url(url) => f.write_str(url),
+            Self::DataUri(uri) => {
+                let header = uri
+                    .split_once(',')
+                    .map_or(uri.as_str(), |(header, _)| header);
+                write!(f, "{},...", header)
+            }
+        }
+    }
+}
+
+/// 是否是 `http(s)://` URL
+fn is_url(source: &str) -> bool {
+    let lower = source.get(..8).unwrap_or(source).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// 从任意数据源读取图片
+///
+/// # 参数
+/// * `reader` - 图片数据，读到结束为止
+///
+/// # 返回
+/// * `Result<Vec<u8>>` - 图片的原始字节
+pub fn read_image(mut reader: impl Read) -> Result<Vec<u8>> {
+    let mut image = Vec::new();
+    reader.read_to_end(&mut image).map_err(RsOcrError::Io)?;
+    Ok(image)
+}
+
+/// 使用默认的HTTP客户端下载图片，见 [`download_image_with`]
+pub fn download_image(url: &str, max_size: u64) -> Result<Vec<u8>> {
+    download_image_with(shared_http_client(), url, max_size)
+}
+
+/// 下载图片
+///
+/// 服务器声明的 `Content-Length` 或实际读取的字节数超过 `max_size` 时停止下载。
+///
+/// # 参数
+/// * `client` - HTTP客户端，决定代理、证书、超时和请求头
+/// * `url` - `http://` 或 `https://` URL
+/// * `max_size` - 图片大小上限（字节）
+///
+/// # 返回
+/// * `Result<Vec<u8>>` - 请求失败、状态码不是2xx或超过大小上限时返回 [`RsOcrError::ImageProcessing`]
+pub fn download_image_with(client: &Client, url: &str, max_size: u64) -> Result<Vec<u8>> {
+    let error =
+        |reason: String| RsOcrError::ImageProcessing(format!("下载图片失败 {}: {}", url, reason));
+    let too_large = || error(format!("超过大小上限 {} 字节", max_size));
+
+    let response = client.get(url).send().map_err(|e| error(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(error(format!("HTTP状态码 {}", response.status())));
+    }
+    if response.content_length().is_some_and(|len| len > max_size) {
+        return Err(too_large());
+    }
+
+    let mut image = Vec::new();
+    response
+        .take(max_size + 1)
+        .read_to_end(&mut image)
+        .map_err(|e| error(e.to_string()))?;
+    if image.len() as u64 > max_size {
+        return Err(too_large());
+    }
+    Ok(image)
+}
+
+/// 解析 `data:image/...;base64,...` URI
+///
+/// # 参数
+/// * `uri` - data URI，媒体类型必须是 `image/*`，数据必须是base64编码
+///
+/// # 返回
+/// * `Result<Vec<u8>>` - 格式不符时返回 [`RsOcrError::ImageProcessing`]，
+///   数据无法解码时返回 [`RsOcrError::Base64`]
+pub fn decode_data_uri(uri: &str) -> Result<Vec<u8>> {
+    let invalid = |reason: &str| RsOcrError::ImageProcessing(format!("无效的data URI: {}", reason));
+
+    let rest = uri
+        .strip_prefix("data:")
+        .ok_or_else(|| invalid("缺少 data: 前缀"))?;
+    let (header, data) = rest.split_once(',').ok_or_else(|| invalid("缺少逗号"))?;
+    let mut params = header.split(';');
+    let media_type = params.next().unwrap_or_default();
+    if !media_type.to_ascii_lowercase().starts_with("image/") {
+        return Err(invalid(&format!("媒体类型 '{}' 不是图片", media_type)));
+    }
+    if !params.any(|p| p.eq_ignore_ascii_case("base64")) {
+        return Err(invalid("只支持base64编码的数据"));
+    }
+
+    let data: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    Ok(general_purpose::STANDARD.decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[test]
+    fn test_parse_sources() {
+        assert_eq!(ImageSource::parse("-"), ImageSource::Stdin);
+        assert_eq!(
+            ImageSource::parse("HTTPS://example.com/a.png"),
+            ImageSource::Url("HTTPS://example.com/a.png".to_string())
+        );
+        assert_eq!(
+            ImageSource::parse("./-"),
+            ImageSource::Path(PathBuf::from("./-"))
+        );
+        assert_eq!(
+            ImageSource::parse("http.png"),
+            ImageSource::Path(PathBuf::from("http.png"))
+        );
+
+        let uri = ImageSource::parse("data:image/png;base64,aGVsbG8=");
+        assert_eq!(uri.to_string(), "data:image/png;base64,...");
+        assert_eq!(ImageSource::Stdin.to_string(), "标准输入");
+    }
+
+    #[test]
+    fn test_decode_data_uri() -> Result<()> {
+        assert_eq!(
+            decode_data_uri("data:image/jpeg;base64,aGVs\nbG8=")?,
+            b"hello"
+        );
+        assert_eq!(
+            decode_data_uri("data:IMAGE/PNG;name=a.png;BASE64,aGVsbG8=")?,
+            b"hello"
+        );
+
+        for uri in [
+            "image/png;base64,aGVsbG8=",
+            "data:text/plain;base64,aGVsbG8=",
+            "data:image/png,hello",
+            "data:image/png;base64",
+        ] {
+            assert!(
+                matches!(decode_data_uri(uri), Err(RsOcrError::ImageProcessing(_))),
+                "{}",
+                uri
+            );
+        }
+        assert!(matches!(
+            decode_data_uri("data:image/png;base64,!!!"),
+            Err(RsOcrError::Base64(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_image() -> Result<()> {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/scan.png" => MockResponse::text(200, "png-bytes"),
+            "/empty.png" => MockResponse::text(200, ""),
+            _ => MockResponse::text(404, "not found"),
+        });
+
+        let url = format!("{}/scan.png", server.url());
+        assert_eq!(download_image(&url, 1024)?, b"png-bytes");
+        assert_eq!(ImageSource::parse(&url).read()?, b"png-bytes");
+
+        let err = download_image(&url, 4).unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(ref m) if m.contains("大小上限")));
+
+        let err = download_image(&format!("{}/missing.png", server.url()), 1024).unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(ref m) if m.contains("404")));
+
+        let empty = ImageSource::parse(&format!("{}/empty.png", server.url()));
+        assert!(matches!(empty.read(), Err(RsOcrError::ImageProcessing(_))));
+        Ok(())
+    }
+}
@@ -12,26 +12,64 @@
 //!     let base64_code = image_to_base64("image.png")?;
 //!     
 //!     // 调用 OCR API
-//!     let ocr_response = call_ocr_api(&base64_code, "http://127.0.0.1:1224/api/ocr")?;
+//!     let ocr_result = call_ocr_api(&base64_code, "http://127.0.0.1:1224/api/ocr")?;
 //!     
-//!     println!("OCR 结果: {:#?}", ocr_response.extra);
+//!     println!("OCR 结果: {}", ocr_result.text());
+//!     for block in &ocr_result.blocks {
+//!         println!("{:?} {:.2} {}", block.bbox, block.score, block.text);
+//!     }
 //!     Ok(())
 //! }
 //! ```
 
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod auth;
+pub mod backend;
+pub mod capabilities;
 pub mod cli;
+pub mod client;
 pub mod config;
 pub mod gui;
+pub mod image_format;
+pub mod input;
 pub mod logger;
 pub mod models;
 pub mod ocr_utils; // GUI 模块，用于 GUI 应用程序
+pub mod pages;
+pub mod preprocess;
+pub mod request_body;
+pub mod retry;
+
+mod unix_socket;
+
+#[cfg(test)]
+mod test_support;
 
 // 重新导出主要功能，方便用户使用
-pub use cli::Args;
-pub use config::Config;
+#[cfg(feature = "async")]
+pub use async_client::{
+    AsyncOcrClient, call_ocr_api_async, call_ocr_api_with_options_async, check_health_async,
+    recognize_file_async,
+};
+pub use backend::{OcrBackend, create_backend};
+pub use capabilities::{OptionChoice, ServerCapabilities, get_capabilities};
+pub use cli::{Args, Command, ConfigCommand};
+pub use client::{OcrClient, OcrClientBuilder};
+pub use config::{Config, OcrConfig};
+pub use input::ImageSource;
 pub use logger::{RsOcrLogger, debug, error, info, warn};
-pub use models::{OcrOptions, OcrRequest, OcrResponse, Result, RsOcrError};
-pub use ocr_utils::{call_ocr_api, image_to_base64};
+pub use models::{
+    DataFormat, IgnoreArea, OcrOptions, OcrRequest, OcrResponse, OcrResult, PageResult, Result,
+    RsOcrError, TbpuParser, TextBlock,
+};
+pub use ocr_utils::{
+    bytes_to_base64, call_ocr_api, call_ocr_api_with_options, check_health, collect_images,
+    data_uri_to_base64, image_to_base64, preprocess_image_to_base64, reader_to_base64,
+    recognize_file, url_to_base64,
+};
+pub use pages::PageRange;
+pub use retry::{CircuitBreaker, RetryPolicy};
 
 /// 库版本信息
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,4 +1,4 @@
-use rsocr::gui;
+use rsocr::{Args, cli, gui};
 
 fn main() {
     // 初始化tracing订阅器来过滤zbus的调试信息
@@ -11,6 +11,15 @@ fn main() {
         .with_writer(std::io::stderr)
         .try_init();
 
-    // 直接运行GUI
+    // 提供了OCR参数时以命令行模式运行，否则启动GUI
+    let args = Args::parse_args();
+    if args.is_headless() {
+        std::process::exit(cli::run(args));
+    }
+    if let Err(e) = args.check_gui() {
+        eprintln!("错误: {}", e);
+        std::process::exit(cli::EXIT_USAGE);
+    }
+
     gui::run();
 }
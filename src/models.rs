@@ -4,11 +4,131 @@
 
 use serde::{Deserialize, Serialize};
 
+/// 结果数据格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    /// 包含文本块、置信度和坐标的结构化结果
+    #[default]
+    Dict,
+    /// 纯文本结果
+    Text,
+}
+
+/// 排版解析方案（Umi-OCR 的 `tbpu.parser`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum TbpuParser {
+    /// 多栏，按自然段换行
+    MultiPara,
+    /// 多栏，总是换行
+    MultiLine,
+    /// 多栏，无换行
+    MultiNone,
+    /// 单栏，按自然段换行
+    SinglePara,
+    /// 单栏，总是换行
+    SingleLine,
+    /// 单栏，无换行
+    SingleNone,
+    /// 单栏，保留缩进（适用于代码）
+    SingleCode,
+    /// 竖排文字（从右到左）
+    VerticalRl,
+    /// 不做排版处理
+    None,
+}
+
+impl TbpuParser {
+    /// 所有排版解析方案
+    pub const ALL: [TbpuParser; 9] = [
+        TbpuParser::MultiPara,
+        TbpuParser::MultiLine,
+        TbpuParser::MultiNone,
+        TbpuParser::SinglePara,
+        TbpuParser::SingleLine,
+        TbpuParser::SingleNone,
+        TbpuParser::SingleCode,
+        TbpuParser::VerticalRl,
+        TbpuParser::None,
+    ];
+
+    /// 发送给服务器的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TbpuParser::MultiPara => "multi_para",
+            TbpuParser::MultiLine => "multi_line",
+            TbpuParser::MultiNone => "multi_none",
+            TbpuParser::SinglePara => "single_para",
+            TbpuParser::SingleLine => "single_line",
+            TbpuParser::SingleNone => "single_none",
+            TbpuParser::SingleCode => "single_code",
+            TbpuParser::VerticalRl => "vertical_rl",
+            TbpuParser::None => "none",
+        }
+    }
+
+    /// 根据服务器名称查找排版解析方案
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name)
+    }
+}
+
+/// 忽略区域：矩形的左上角和右下角像素坐标
+pub type IgnoreArea = [[i32; 2]; 2];
+
+/// 解析忽略区域
+///
+/// # 参数
+/// * `value` - 格式为 `x0,y0,x1,y1` 的字符串
+///
+/// # 返回
+/// * `Result<IgnoreArea>` - 成功时返回忽略区域，格式错误时返回 [`RsOcrError::Config`]
+pub fn parse_ignore_area(value: &str) -> Result<IgnoreArea> {
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<i32>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| RsOcrError::Config(format!("忽略区域格式错误 '{}': {}", value, e)))?;
+
+    match numbers[..] {
+        [x0, y0, x1, y1] => Ok([[x0, y0], [x1, y1]]),
+        _ => Err(RsOcrError::Config(format!(
+            "忽略区域格式错误 '{}': 应为 x0,y0,x1,y1",
+            value
+        ))),
+    }
+}
+
 /// OCR请求选项
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// 字段与 Umi-OCR 的参数一一对应，未设置的字段不会发送，由服务器使用默认值。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct OcrOptions {
+    /// 结果数据格式
     #[serde(rename = "data.format")]
-    pub data_format: String,
+    pub data_format: DataFormat,
+
+    /// 识别语言（模型配置），如 `models/config_chinese.txt`
+    #[serde(rename = "ocr.language", skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// 是否启用文字方向纠正
+    #[serde(rename = "ocr.cls", skip_serializing_if = "Option::is_none")]
+    pub cls: Option<bool>,
+
+    /// 图片最长边的限制，超出时服务器会缩小图片
+    #[serde(rename = "ocr.limit_side_len", skip_serializing_if = "Option::is_none")]
+    pub limit_side_len: Option<u32>,
+
+    /// 排版解析方案
+    #[serde(rename = "tbpu.parser", skip_serializing_if = "Option::is_none")]
+    pub parser: Option<TbpuParser>,
+
+    /// 忽略区域，落在其中的文本块会被丢弃
+    #[serde(rename = "tbpu.ignoreArea", skip_serializing_if = "Option::is_none")]
+    pub ignore_area: Option<Vec<IgnoreArea>>,
 }
 
 /// OCR请求
@@ -18,17 +138,201 @@ pub struct OcrRequest {
     pub options: OcrOptions,
 }
 
+/// Umi-OCR 结果码：识别成功
+pub const CODE_SUCCESS: i64 = 100;
+
+/// Umi-OCR 结果码：图片中没有文字
+pub const CODE_NO_TEXT: i64 = 101;
+
 /// OCR响应
+///
+/// 以 `data.format = "dict"` 请求时 `data` 为文本块数组，出错时为错误信息字符串。
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OcrResponse {
+    /// 结果码，见 [`CODE_SUCCESS`]、[`CODE_NO_TEXT`]，其他值表示识别失败
+    #[serde(default)]
+    pub code: Option<i64>,
+
     /// OCR识别结果数据
-    pub data: Option<String>,
+    pub data: Option<serde_json::Value>,
+
+    /// 识别耗时（秒）
+    #[serde(default)]
+    pub time: Option<f64>,
+
+    /// 识别完成的时间戳
+    #[serde(default)]
+    pub timestamp: Option<f64>,
 
     // 其他可能的字段使用泛型结构以适配不同响应
     #[serde(flatten)]
     pub extra: serde_json::Value,
 }
 
+impl OcrResponse {
+    /// 将响应转换为结构化的识别结果
+    ///
+    /// 结果码为 [`CODE_NO_TEXT`] 时返回没有文本块的结果，而不是错误。
+    ///
+    /// # 返回
+    /// * `Result<OcrResult>` - 成功时返回识别结果；结果码表示失败时返回
+    ///   [`RsOcrError::OcrServer`]，其中包含服务器的错误信息
+    pub fn into_result(self) -> Result<OcrResult> {
+        match self.code {
+            None | Some(CODE_SUCCESS) => {}
+            Some(CODE_NO_TEXT) => {
+                return Ok(OcrResult {
+                    blocks: Vec::new(),
+                    time: self.time,
+                    timestamp: self.timestamp,
+                    rotation: None,
+                });
+            }
+            Some(code) => {
+                let message = match self.data {
+                    Some(serde_json::Value::String(message)) => message,
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                return Err(RsOcrError::OcrServer { code, message });
+            }
+        }
+
+        let blocks = match self.data {
+            Some(serde_json::Value::Array(items)) => items
+                .into_iter()
+                .map(serde_json::from_value)
+                .collect::<std::result::Result<Vec<TextBlock>, _>>()
+                .map_err(RsOcrError::Json)?,
+            Some(serde_json::Value::String(message)) => return Err(RsOcrError::OcrApi(message)),
+            Some(other) => {
+                return Err(RsOcrError::OcrApi(format!(
+                    "无法识别的OCR结果格式: {}",
+                    other
+                )));
+            }
+            None => return Err(RsOcrError::OcrApi("OCR API返回空结果".to_string())),
+        };
+
+        Ok(OcrResult {
+            blocks,
+            time: self.time,
+            timestamp: self.timestamp,
+            rotation: None,
+        })
+    }
+}
+
+/// 识别出的文本块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextBlock {
+    /// 文本内容
+    pub text: String,
+
+    /// 置信度，范围 0 到 1
+    pub score: f64,
+
+    /// 文本框四个顶点的像素坐标，顺序为左上、右上、右下、左下
+    #[serde(rename = "box")]
+    pub bbox: [[i32; 2]; 4],
+
+    /// 文本块结尾的分隔符（如换行符、空格或空字符串）
+    #[serde(default)]
+    pub end: String,
+}
+
+/// 结构化的OCR识别结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OcrResult {
+    /// 识别出的文本块
+    pub blocks: Vec<TextBlock>,
+
+    /// 识别耗时（秒）
+    pub time: Option<f64>,
+
+    /// 识别完成的时间戳
+    pub timestamp: Option<f64>,
+
+    /// 自动检测方向时选用的顺时针旋转角度（0、90、180或270），未检测时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<u32>,
+}
+
+impl OcrResult {
+    /// 按文本块的结尾分隔符拼接全部文本
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for (i, block) in self.blocks.iter().enumerate() {
+            text.push_str(&block.text);
+            // 最后一个文本块的分隔符不再追加，避免结果末尾多出换行
+            if i + 1 < self.blocks.len() {
+                text.push_str(&block.end);
+            }
+        }
+        text
+    }
+
+    /// 是否没有识别出任何文本
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// 全部文本块置信度的平均值，没有文本块时为0
+    pub fn mean_score(&self) -> f64 {
+        if self.blocks.is_empty() {
+            return 0.0;
+        }
+        self.blocks.iter().map(|block| block.score).sum::<f64>() / self.blocks.len() as f64
+    }
+
+    /// 缩放全部文本框的坐标，如把预处理后图片上的坐标换算回原图
+    ///
+    /// # 参数
+    /// * `sx` - 横坐标的倍数
+    /// * `sy` - 纵坐标的倍数
+    pub fn scale_boxes(&mut self, sx: f64, sy: f64) {
+        for block in &mut self.blocks {
+            for [x, y] in &mut block.bbox {
+                *x = (*x as f64 * sx).round() as i32;
+                *y = (*y as f64 * sy).round() as i32;
+            }
+        }
+    }
+
+    /// 把顺时针旋转后图片上的坐标换算回旋转前的图片
+    ///
+    /// 顶点顺序不变，仍以文字本身的方向为准。
+    ///
+    /// # 参数
+    /// * `rotation` - 顺时针旋转的角度，只支持 90、180 和 270，其他值不做换算
+    /// * `width` - 旋转前图片的宽度
+    /// * `height` - 旋转前图片的高度
+    pub fn unrotate_boxes(&mut self, rotation: u32, width: u32, height: u32) {
+        let (width, height) = (width as i32, height as i32);
+        for block in &mut self.blocks {
+            for point in &mut block.bbox {
+                let [x, y] = *point;
+                *point = match rotation {
+                    90 => [y, height - x],
+                    180 => [width - x, height - y],
+                    270 => [width - y, x],
+                    _ => [x, y],
+                };
+            }
+        }
+    }
+}
+
+/// 多页图片中一页的识别结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageResult {
+    /// 页码，从1开始
+    pub page: usize,
+
+    /// 这一页的识别结果
+    pub result: OcrResult,
+}
+
 /// 错误类型
 #[derive(Debug, thiserror::Error)]
 pub enum RsOcrError {
@@ -50,6 +354,12 @@ pub enum RsOcrError {
     #[error("OCR API错误: {0}")]
     OcrApi(String),
 
+    #[error("OCR服务错误 (code {code}): {message}")]
+    OcrServer { code: i64, message: String },
+
+    #[error("OCR服务暂时不可用（熔断中），{0:?}后重试")]
+    CircuitOpen(std::time::Duration),
+
     #[error("图片处理错误: {0}")]
     ImageProcessing(String),
 
@@ -62,3 +372,159 @@ pub enum RsOcrError {
 
 /// 结果类型别名
 pub type Result<T> = std::result::Result<T, RsOcrError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dict_response_into_result() -> Result<()> {
+        let json = r#"{
+            "code": 100,
+            "data": [
+                {"text": "第一行", "score": 0.99, "box": [[1, 2], [30, 2], [30, 12], [1, 12]], "end": "\n"},
+                {"text": "第二行", "score": 0.95, "box": [[1, 20], [30, 20], [30, 32], [1, 32]], "end": "\n"}
+            ],
+            "time": 0.25,
+            "timestamp": 1700000000.5
+        }"#;
+
+        let response: OcrResponse = serde_json::from_str(json)?;
+        let result = response.into_result()?;
+
+        assert_eq!(result.blocks.len(), 2);
+        assert_eq!(result.blocks[0].bbox[2], [30, 12]);
+        assert_eq!(result.time, Some(0.25));
+        assert_eq!(result.text(), "第一行\n第二行");
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_text_code() -> Result<()> {
+        let response: OcrResponse =
+            serde_json::from_str(r#"{"code": 101, "data": "", "time": 0.1}"#)?;
+        let result = response.into_result()?;
+        assert!(result.is_empty());
+        assert_eq!(result.text(), "");
+        assert_eq!(result.time, Some(0.1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_failure_code() -> Result<()> {
+        let response: OcrResponse =
+            serde_json::from_str(r#"{"code": 902, "data": "[Error] Base64 decoding failed."}"#)?;
+        match response.into_result() {
+            Err(RsOcrError::OcrServer { code, message }) => {
+                assert_eq!(code, 902);
+                assert_eq!(message, "[Error] Base64 decoding failed.");
+            }
+            other => panic!("应返回 OcrServer 错误: {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_response_is_error() -> Result<()> {
+        let response: OcrResponse = serde_json::from_str(r#"{"code": 102, "data": "识别失败"}"#)?;
+        let err = response.into_result().unwrap_err();
+        assert!(err.to_string().contains("识别失败"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_options_serialization() -> Result<()> {
+        let options = OcrOptions::default();
+        assert_eq!(
+            serde_json::to_value(&options)?,
+            serde_json::json!({"data.format": "dict"})
+        );
+
+        let options = OcrOptions {
+            language: Some("models/config_en.txt".to_string()),
+            cls: Some(true),
+            limit_side_len: Some(960),
+            parser: Some(TbpuParser::SingleCode),
+            ignore_area: Some(vec![[[0, 0], [100, 20]]]),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&options)?,
+            serde_json::json!({
+                "data.format": "dict",
+                "ocr.language": "models/config_en.txt",
+                "ocr.cls": true,
+                "ocr.limit_side_len": 960,
+                "tbpu.parser": "single_code",
+                "tbpu.ignoreArea": [[[0, 0], [100, 20]]]
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_names() {
+        for parser in TbpuParser::ALL {
+            let json = serde_json::to_value(parser).unwrap();
+            assert_eq!(json, serde_json::json!(parser.as_str()));
+            assert_eq!(TbpuParser::from_name(parser.as_str()), Some(parser));
+        }
+    }
+
+    #[test]
+    fn test_parse_ignore_area() {
+        assert_eq!(
+            parse_ignore_area("0, 0,100,20").unwrap(),
+            [[0, 0], [100, 20]]
+        );
+        assert!(parse_ignore_area("0,0,100").is_err());
+        assert!(parse_ignore_area("a,b,c,d").is_err());
+    }
+
+    #[test]
+    fn test_rotation_helpers() {
+        let block = |score: f64, bbox: [[i32; 2]; 4]| TextBlock {
+            text: "字".to_string(),
+            score,
+            bbox,
+            end: String::new(),
+        };
+        // 100×40 的图片顺时针旋转90°后为 40×100，原图 (10, 5) 落在 (35, 10)
+        let mut result = OcrResult {
+            blocks: vec![
+                block(0.9, [[35, 10], [35, 30], [25, 30], [25, 10]]),
+                block(0.5, [[0, 0]; 4]),
+            ],
+            ..Default::default()
+        };
+        assert!((result.mean_score() - 0.7).abs() < 1e-9);
+        assert_eq!(OcrResult::default().mean_score(), 0.0);
+
+        result.unrotate_boxes(90, 100, 40);
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 5], [30, 5], [30, 15], [10, 15]]
+        );
+
+        let mut result = OcrResult {
+            blocks: vec![block(1.0, [[90, 35], [70, 35], [70, 25], [90, 25]])],
+            ..Default::default()
+        };
+        result.unrotate_boxes(180, 100, 40);
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 5], [30, 5], [30, 15], [10, 15]]
+        );
+
+        let mut result = OcrResult {
+            blocks: vec![block(1.0, [[5, 90], [5, 70], [15, 70], [15, 90]])],
+            ..Default::default()
+        };
+        result.unrotate_boxes(270, 100, 40);
+        assert_eq!(
+            result.blocks[0].bbox,
+            [[10, 5], [30, 5], [30, 15], [10, 15]]
+        );
+        assert!(!serde_json::to_string(&result).unwrap().contains("rotation"));
+    }
+}
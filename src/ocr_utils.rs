@@ -1,11 +1,24 @@
-use crate::models::{OcrRequest, OcrResponse, Result, RsOcrError};
+use crate::client::OcrClient;
+use crate::input::{self, DEFAULT_MAX_DOWNLOAD_SIZE};
+use crate::models::{OcrOptions, OcrResult, Result, RsOcrError};
+use crate::preprocess::Pipeline;
+use crate::request_body::Base64Reader;
 use base64::{Engine as _, engine::general_purpose};
-use reqwest::blocking::Client;
 use std::fs::File;
 use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 支持识别的图片扩展名
+pub const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "gif", "tif", "tiff", "webp", "pbm", "pgm", "ppm", "pnm", "pam",
+    "ico", "tga",
+];
 
 /// 将图片文件转换为base64编码
 ///
+/// 整个文件和编码结果都会读入内存。只需要发送请求时，[`recognize_file`] 使用
+/// [`OcrRequestBody`](crate::request_body::OcrRequestBody) 流式生成请求体，内存占用更小。
+///
 /// # 参数
 /// * `image_path` - 图片文件路径
 ///
@@ -20,47 +33,191 @@ pub fn image_to_base64(image_path: &str) -> Result<String> {
     Ok(base64_code)
 }
 
+/// 预处理图片文件后转换为base64编码
+///
+/// 处理后的图片按流程设置的格式重新编码（默认PNG）。需要把识别结果换算回原图坐标时，使用
+/// [`PreprocessBackend`](crate::backend::PreprocessBackend)。
+///
+/// # 参数
+/// * `image_path` - 图片文件路径
+/// * `pipeline` - 预处理流程，为空时与 [`image_to_base64`] 相同
+///
+/// # 返回
+/// * `Result<String>` - 图片无法解码时返回 [`RsOcrError::ImageProcessing`]
+pub fn preprocess_image_to_base64(image_path: &str, pipeline: &Pipeline) -> Result<String> {
+    if pipeline.is_empty() {
+        return image_to_base64(image_path);
+    }
+    let image = std::fs::read(image_path).map_err(RsOcrError::Io)?;
+    Ok(bytes_to_base64(&pipeline.process(&image)?.data))
+}
+
+/// 将图片数据转换为base64编码
+///
+/// # 参数
+/// * `image` - 图片的原始字节
+///
+/// # 返回
+/// * `String` - base64编码字符串
+pub fn bytes_to_base64(image: &[u8]) -> String {
+    general_purpose::STANDARD.encode(image)
+}
+
+/// 读取图片数据并转换为base64编码，如标准输入或网络流
+///
+/// 数据边读取边编码，不会同时保存原始数据和编码结果。
+///
+/// # 参数
+/// * `reader` - 图片数据，读到结束为止
+///
+/// # 返回
+/// * `Result<String>` - 成功时返回base64编码字符串，读取失败时返回错误
+pub fn reader_to_base64(reader: impl Read) -> Result<String> {
+    let mut base64_code = String::new();
+    Base64Reader::new(reader)
+        .read_to_string(&mut base64_code)
+        .map_err(RsOcrError::Io)?;
+    Ok(base64_code)
+}
+
+/// 下载图片并转换为base64编码
+///
+/// 图片大小上限为 [`DEFAULT_MAX_DOWNLOAD_SIZE`]，需要其他上限时使用 [`input::download_image`]。
+///
+/// # 参数
+/// * `url` - 图片的 `http://` 或 `https://` URL
+///
+/// # 返回
+/// * `Result<String>` - 成功时返回base64编码字符串，下载失败或超过大小上限时返回错误
+pub fn url_to_base64(url: &str) -> Result<String> {
+    let image = input::download_image(url, DEFAULT_MAX_DOWNLOAD_SIZE)?;
+    Ok(bytes_to_base64(&image))
+}
+
+/// 取出 `data:image/...;base64,` URI中的base64编码
+///
+/// # 参数
+/// * `uri` - data URI
+///
+/// # 返回
+/// * `Result<String>` - 成功时返回base64编码字符串，URI不是base64编码的图片时返回错误
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::data_uri_to_base64;
+///
+/// let base64_code = data_uri_to_base64("data:image/png;base64,aGVsbG8=").unwrap();
+/// assert_eq!(base64_code, "aGVsbG8=");
+/// ```
+pub fn data_uri_to_base64(uri: &str) -> Result<String> {
+    let image = input::decode_data_uri(uri)?;
+    Ok(bytes_to_base64(&image))
+}
+
 /// 调用OCR API进行文字识别
 ///
+/// 这是 [`OcrClient`] 的简便封装，所有调用共享同一个连接池，并使用默认的超时设置，
+/// 代理取自 `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` 环境变量。需要自定义超时、请求头、
+/// 代理或证书时请使用 [`OcrClient::builder`]。
+///
 /// # 参数
 /// * `base64_code` - 图片的base64编码
 /// * `url` - OCR API的URL
 ///
 /// # 返回
-/// * `Result<OcrResponse>` - 成功时返回OCR响应，失败时返回错误
-pub fn call_ocr_api(base64_code: &str, url: &str) -> Result<OcrResponse> {
-    let request_data = OcrRequest {
-        base64: base64_code.to_string(),
-        options: crate::models::OcrOptions {
-            data_format: "text".to_string(),
-        },
-    };
-
-    let client = Client::new();
-    let response = client
-        .post(url)
-        .header("Content-Type", "application/json")
-        .json(&request_data)
-        .send()
-        .map_err(RsOcrError::Http)?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_msg = format!(
-            "OCR API请求失败: HTTP状态码 {} ({})",
-            status,
-            status.canonical_reason().unwrap_or("未知错误")
-        );
-        return Err(RsOcrError::OcrApi(error_msg));
+/// * `Result<OcrResult>` - 成功时返回包含文本块、置信度和坐标的识别结果，失败时返回错误
+pub fn call_ocr_api(base64_code: &str, url: &str) -> Result<OcrResult> {
+    call_ocr_api_with_options(base64_code, url, &OcrOptions::default())
+}
+
+/// 使用指定选项调用OCR API进行文字识别
+///
+/// 为了得到结构化结果，请求总是以 [`DataFormat::Dict`](crate::models::DataFormat::Dict) 格式发送，
+/// 忽略 `options.data_format` 的值。
+///
+/// # 参数
+/// * `base64_code` - 图片的base64编码
+/// * `url` - OCR API的URL
+/// * `options` - OCR请求选项
+///
+/// # 返回
+/// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+pub fn call_ocr_api_with_options(
+    base64_code: &str,
+    url: &str,
+    options: &OcrOptions,
+) -> Result<OcrResult> {
+    OcrClient::shared(url)?.recognize_with_options(base64_code, options)
+}
+
+/// 识别图片文件中的文字
+///
+/// 与 [`image_to_base64`] 加 [`call_ocr_api_with_options`] 的结果相同，
+/// 但请求体边读取文件边编码，不会把整个文件读入内存。
+///
+/// # 参数
+/// * `image_path` - 图片文件路径
+/// * `url` - OCR API的URL
+/// * `options` - OCR请求选项
+///
+/// # 返回
+/// * `Result<OcrResult>` - 成功时返回识别结果，失败时返回错误
+pub fn recognize_file(image_path: &str, url: &str, options: &OcrOptions) -> Result<OcrResult> {
+    OcrClient::shared(url)?.recognize_file_with_options(image_path, options)
+}
+
+/// 判断路径是否为支持的图片文件（按扩展名判断）
+pub fn is_image_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// 收集目录中的图片文件
+///
+/// # 参数
+/// * `dir` - 要扫描的目录
+/// * `recursive` - 是否递归扫描子目录
+///
+/// # 返回
+/// * `Result<Vec<PathBuf>>` - 按路径排序的图片文件列表
+pub fn collect_images(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+
+    for entry in std::fs::read_dir(dir).map_err(RsOcrError::Io)? {
+        let path = entry.map_err(RsOcrError::Io)?.path();
+        if path.is_dir() {
+            if recursive {
+                images.extend(collect_images(&path, recursive)?);
+            }
+        } else if is_image_file(&path) {
+            images.push(path);
+        }
     }
 
-    let ocr_response: OcrResponse = response.json().map_err(RsOcrError::Http)?;
-    Ok(ocr_response)
+    images.sort();
+    Ok(images)
+}
+
+/// 检查OCR服务是否可访问
+///
+/// 向API地址发送GET请求，只要服务有响应且不是服务器错误（5xx）即视为可用。
+///
+/// # 参数
+/// * `url` - OCR API的URL
+///
+/// # 返回
+/// * `Result<()>` - 服务可用时返回 `Ok(())`，否则返回错误
+pub fn check_health(url: &str) -> Result<()> {
+    OcrClient::shared(url)?.health()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{MockResponse, MockServer};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -96,4 +253,60 @@ mod tests {
         let result = image_to_base64("/non/existent/path/image.png");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_collect_images() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new().map_err(RsOcrError::Io)?;
+        let sub_dir = temp_dir.path().join("sub");
+        std::fs::create_dir(&sub_dir).map_err(RsOcrError::Io)?;
+
+        for name in ["b.png", "a.JPG", "notes.txt"] {
+            std::fs::write(temp_dir.path().join(name), b"x").map_err(RsOcrError::Io)?;
+        }
+        std::fs::write(sub_dir.join("c.bmp"), b"x").map_err(RsOcrError::Io)?;
+
+        let images = collect_images(temp_dir.path(), false)?;
+        assert_eq!(
+            images,
+            vec![temp_dir.path().join("a.JPG"), temp_dir.path().join("b.png")]
+        );
+
+        let images = collect_images(temp_dir.path(), true)?;
+        assert_eq!(images.len(), 3);
+        assert!(images.contains(&sub_dir.join("c.bmp")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_ocr_api_result_codes() -> Result<()> {
+        let server = MockServer::start(|request| match request.json()["base64"].as_str() {
+            Some("ok") => MockResponse::umi_text("你好", 0.9),
+            Some("empty") => MockResponse::json(serde_json::json!({"code": 101, "data": ""})),
+            _ => MockResponse::json(serde_json::json!({"code": 902, "data": "图片解码失败"})),
+        });
+        let url = format!("{}/api/ocr", server.url());
+
+        let result = call_ocr_api("ok", &url)?;
+        assert_eq!(result.text(), "你好");
+
+        let result = call_ocr_api("empty", &url)?;
+        assert!(result.is_empty());
+
+        let err = call_ocr_api("bad", &url).unwrap_err();
+        assert!(matches!(err, RsOcrError::OcrServer { code: 902, .. }));
+        assert!(err.to_string().contains("图片解码失败"));
+
+        // 请求总是以 dict 格式发送
+        let request = &server.requests()[0];
+        assert_eq!(request.json()["options"]["data.format"], "dict");
+
+        // 格式错误的 `unix://` 地址直接报告配置错误
+        assert!(matches!(
+            call_ocr_api("aGVsbG8=", "unix://"),
+            Err(RsOcrError::Config(_))
+        ));
+
+        Ok(())
+    }
 }
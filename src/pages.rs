@@ -0,0 +1,452 @@
+//! 多页图片的拆分
+//!
+//! 传真和归档的扫描件常是多页TIFF，GIF动画也有多帧，而OCR服务只识别第一帧。
+//! [`split_pages`] 按 [`PageRange`] 把这些图片拆成单页，
+//! [`OcrBackend::recognize_pages`](crate::OcrBackend::recognize_pages) 逐页识别。
+//! GIF多是动画而不是文档，未指定页码时只取第1帧。
+
+use crate::image_format::{self, ImageKind};
+use crate::models::{Result, RsOcrError};
+use image::codecs::gif::GifDecoder;
+use image::{AnimationDecoder, DynamicImage, ImageFormat};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Cursor;
+use std::str::FromStr;
+
+/// 最多拆分的页数，避免损坏的TIFF文件导致过多的解码
+pub const MAX_PAGES: usize = 10_000;
+
+/// 页码范围，页码从1开始
+///
+/// 格式为逗号分隔的页码或范围，如 `1-3,5,8-`，其中 `8-` 表示第8页到最后一页。
+/// 默认值表示未指定页码：TIFF取全部页面，GIF动画只取第1帧；需要GIF的全部帧时写作 `1-`。
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::pages::PageRange;
+///
+/// let range: PageRange = "1-3,5,8-".parse().unwrap();
+/// assert!(range.contains(2));
+/// assert!(!range.contains(4));
+/// assert!(range.contains(100));
+/// assert!(PageRange::all().contains(4));
+/// assert!("0".parse::<PageRange>().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageRange {
+    /// 起止页码（包含两端），没有终止页码时到最后一页；为空时表示全部页面
+    spans: Vec<(usize, Option<usize>)>,
+}
+
+impl PageRange {
+    /// 全部页面
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// 是否为未指定页码的默认值
+    pub fn is_all(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// 是否包含指定页
+    pub fn contains(&self, page: usize) -> bool {
+        self.is_all()
+            || self
+                .spans
+                .iter()
+                .any(|&(start, end)| page >= start && end.is_none_or(|end| page <= end))
+    }
+
+    /// 范围内最大的页码，范围没有终点时为 `None`
+    pub fn last(&self) -> Option<usize> {
+        self.spans
+            .iter()
+            .map(|&(_, end)| end)
+            .reduce(|a, b| a.zip(b).map(|(a, b)| a.max(b)))
+            .flatten()
+    }
+
+    /// 检查共 `count` 页的图片中是否有页面落在范围内
+    ///
+    /// # 返回
+    /// * `Result<()>` - 没有页面落在范围内时返回 [`RsOcrError::Config`]
+    pub fn check(&self, count: usize) -> Result<()> {
+        if (1..=count).any(|page| self.contains(page)) {
+            return Ok(());
+        }
+        Err(RsOcrError::Config(format!(
+            "页码范围 {} 超出了图片的页数 {}",
+            self, count
+        )))
+    }
+}
+
+impl FromStr for PageRange {
+    type Err = RsOcrError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let error =
+            |reason: &str| RsOcrError::Config(format!("页码范围格式错误 '{}': {}", value, reason));
+        let page = |s: &str| match s.trim().parse::<usize>() {
+            Ok(0) => Err(error("页码从1开始")),
+            Ok(page) => Ok(page),
+            Err(_) => Err(error("应为页码或范围，如 1-3,5,8-")),
+        };
+
+        let mut spans = Vec::new();
+        for part in value.split(',') {
+            let span = match part.split_once('-') {
+                Some((start, end)) if end.trim().is_empty() => (page(start)?, None),
+                Some((start, end)) => {
+                    let (start, end) = (page(start)?, page(end)?);
+                    if start > end {
+                        return Err(error("起始页码大于终止页码"));
+                    }
+                    (start, Some(end))
+                }
+                None => {
+                    let page = page(part)?;
+                    (page, Some(page))
+                }
+            };
+            spans.push(span);
+        }
+        Ok(Self { spans })
+    }
+}
+
+impl fmt::Display for PageRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_all() {
+            return f.write_str("全部");
+        }
+        let parts: Vec<String> = self
+            .spans
+            .iter()
+            .map(|&(start, end)| match end {
+                Some(end) if end == start => start.to_string(),
+                Some(end) => format!("{}-{}", start, end),
+                None => format!("{}-", start),
+            })
+            .collect();
+        f.write_str(&parts.join(","))
+    }
+}
+
+/// 拆分出的一页图片
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<'a> {
+    /// 页码，从1开始
+    pub number: usize,
+    /// 这一页的图片，只有一页时为原图，否则为PNG
+    pub data: Cow<'a, [u8]>,
+}
+
+/// 按页码范围拆分多页TIFF和GIF动画
+///
+/// 其他格式和只有一页的图片原样返回为第1页，不检查是否为图片。
+///
+/// # 参数
+/// * `data` - 图片的原始字节
+/// * `range` - 要拆分的页码范围
+///
+/// # 返回
+/// * `Result<Vec<Page>>` - 按页码顺序排列的页面；TIFF或GIF无法解码时返回
+///   [`RsOcrError::ImageProcessing`]，没有页面落在范围内时返回 [`RsOcrError::Config`]
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::pages::{PageRange, split_pages};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let fax = std::fs::read("fax.tiff")?;
+///     for page in split_pages(&fax, &"2-".parse()?)? {
+///         std::fs::write(format!("page-{}.png", page.number), &page.data)?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn split_pages<'a>(data: &'a [u8], range: &PageRange) -> Result<Vec<Page<'a>>> {
+    match image_format::sniff(data) {
+        Some(ImageKind::Tiff) => split_tiff(data, range),
+        Some(ImageKind::Gif) => split_gif(data, range),
+        _ => {
+            range.check(1)?;
+            Ok(vec![Page {
+                number: 1,
+                data: Cow::Borrowed(data),
+            }])
+        }
+    }
+}
+
+fn split_tiff<'a>(data: &'a [u8], range: &PageRange) -> Result<Vec<Page<'a>>> {
+    let offsets = tiff_ifd_offsets(data)?;
+    range.check(offsets.len())?;
+    if offsets.len() == 1 {
+        return Ok(vec![Page {
+            number: 1,
+            data: Cow::Borrowed(data),
+        }]);
+    }
+
+    // 把文件头中第一个IFD的偏移改为目标页，解码器就会读取这一页
+    let big_endian = data.starts_with(b"MM");
+    let mut patched = data.to_vec();
+    let mut pages = Vec::new();
+    for (i, &offset) in offsets.iter().enumerate() {
+        let number = i + 1;
+        if !range.contains(number) {
+            continue;
+        }
+        let bytes = if big_endian {
+            offset.to_be_bytes()
+        } else {
+            offset.to_le_bytes()
+        };
+        patched[4..8].copy_from_slice(&bytes);
+        let image = image::load_from_memory_with_format(&patched, ImageFormat::Tiff)
+            .map_err(|e| page_error(number, e))?;
+        pages.push(Page {
+            number,
+            data: Cow::Owned(encode_png(&image, number)?),
+        });
+    }
+    Ok(pages)
+}
+
+/// 沿IFD链读取TIFF中每一页的偏移
+fn tiff_ifd_offsets(data: &[u8]) -> Result<Vec<u32>> {
+    let big_endian = data.starts_with(b"MM");
+    let u16_at = |at: usize| {
+        let bytes: [u8; 2] = data.get(at..at.checked_add(2)?)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |at: usize| {
+        let bytes: [u8; 4] = data.get(at..at.checked_add(4)?)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+    let corrupt = || RsOcrError::ImageProcessing("TIFF文件已损坏: 无法读取页面目录".to_string());
+
+    let mut offsets = Vec::new();
+    let mut seen = HashSet::new();
+    let mut next = u32_at(4).ok_or_else(corrupt)?;
+    // 偏移为0表示最后一页；指回已读过的页面时视为结束，避免死循环
+    while next != 0 && offsets.len() < MAX_PAGES && seen.insert(next) {
+        let entries = u16_at(next as usize).ok_or_else(corrupt)?;
+        offsets.push(next);
+        next = u32_at(next as usize + 2 + entries as usize * 12).unwrap_or(0);
+    }
+    if offsets.is_empty() {
+        return Err(corrupt());
+    }
+    Ok(offsets)
+}
+
+/// 拆分GIF动画，未指定页码时只取第1帧，解码到范围内最后一帧为止
+fn split_gif<'a>(data: &'a [u8], range: &PageRange) -> Result<Vec<Page<'a>>> {
+    let first_frame = PageRange {
+        spans: vec![(1, Some(1))],
+    };
+    let range = if range.is_all() { &first_frame } else { range };
+    let last = range.last();
+
+    let decoder = GifDecoder::new(Cursor::new(data)).map_err(|e| page_error(1, e))?;
+    let mut count = 0;
+    let mut pages = Vec::new();
+    for frame in decoder.into_frames() {
+        if last.is_some_and(|last| count >= last) {
+            break;
+        }
+        count += 1;
+        let frame = frame.map_err(|e| page_error(count, e))?;
+        if range.contains(count) {
+            // 帧已按处置方式合成为完整画面
+            let image = DynamicImage::ImageRgba8(frame.into_buffer());
+            pages.push(Page {
+                number: count,
+                data: Cow::Owned(encode_png(&image, count)?),
+            });
+        }
+    }
+    range.check(count)?;
+    if count == 1 {
+        return Ok(vec![Page {
+            number: 1,
+            data: Cow::Borrowed(data),
+        }]);
+    }
+    Ok(pages)
+}
+
+fn encode_png(image: &DynamicImage, number: usize) -> Result<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| page_error(number, e))?;
+    Ok(png.into_inner())
+}
+
+fn page_error(number: usize, e: image::ImageError) -> RsOcrError {
+    RsOcrError::ImageProcessing(format!("无法解码第 {} 页: {}", number, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, GrayImage, Luma, RgbaImage};
+
+    /// 每页宽度不同的多页灰度TIFF
+    fn multi_page_tiff(shades: &[u8]) -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        let mut encoder = tiff::encoder::TiffEncoder::new(&mut data).unwrap();
+        for (i, &shade) in shades.iter().enumerate() {
+            let width = 4 + i as u32;
+            let pixels = vec![shade; width as usize * 3];
+            encoder
+                .write_image::<tiff::encoder::colortype::Gray8>(width, 3, &pixels)
+                .unwrap();
+        }
+        data.into_inner()
+    }
+
+    fn animated_gif(shades: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = GifEncoder::new(&mut data);
+        for &shade in shades {
+            let frame = RgbaImage::from_pixel(5, 5, image::Rgba([shade, shade, shade, 255]));
+            encoder
+                .encode_frame(Frame::from_parts(
+                    frame,
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                ))
+                .unwrap();
+        }
+        drop(encoder);
+        data
+    }
+
+    fn shade(page: &Page) -> u8 {
+        image::load_from_memory(&page.data)
+            .unwrap()
+            .to_luma8()
+            .get_pixel(0, 0)
+            .0[0]
+    }
+
+    #[test]
+    fn test_page_range() {
+        let range: PageRange = " 2 , 4-5 ,9-".parse().unwrap();
+        assert_eq!(
+            (1..=10).filter(|&p| range.contains(p)).collect::<Vec<_>>(),
+            [2, 4, 5, 9, 10]
+        );
+        assert_eq!(range.to_string(), "2,4-5,9-");
+        assert!(range.check(2).is_ok());
+        assert!(matches!(range.check(1), Err(RsOcrError::Config(_))));
+        assert!(PageRange::all().check(1).is_ok());
+        assert_eq!(range.last(), None);
+        assert_eq!("1-3,5".parse::<PageRange>().unwrap().last(), Some(5));
+        assert_eq!(PageRange::all().last(), None);
+
+        for bad in ["", "a", "3-1", "0-2", "1,,2", "-3"] {
+            assert!(bad.parse::<PageRange>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_split_tiff() {
+        let tiff = multi_page_tiff(&[10, 120, 240]);
+        assert_eq!(tiff_ifd_offsets(&tiff).unwrap().len(), 3);
+
+        let pages = split_pages(&tiff, &PageRange::all()).unwrap();
+        assert_eq!(
+            pages.iter().map(|p| p.number).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(pages.iter().map(shade).collect::<Vec<_>>(), [10, 120, 240]);
+        let third = image::load_from_memory(&pages[2].data).unwrap();
+        assert_eq!(third.width(), 6);
+
+        let pages = split_pages(&tiff, &"2-".parse().unwrap()).unwrap();
+        assert_eq!(pages.iter().map(shade).collect::<Vec<_>>(), [120, 240]);
+        assert!(matches!(
+            split_pages(&tiff, &"4".parse().unwrap()),
+            Err(RsOcrError::Config(_))
+        ));
+
+        // 单页TIFF原样返回
+        let single = multi_page_tiff(&[50]);
+        let pages = split_pages(&single, &PageRange::all()).unwrap();
+        assert!(matches!(pages[0].data, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_split_gif() {
+        let gif = animated_gif(&[0, 200, 100]);
+        let pages = split_pages(&gif, &"1-".parse().unwrap()).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(shade(&pages[1]), 200);
+        assert!(pages[1].data.starts_with(b"\x89PNG"));
+
+        // 未指定页码时只取第1帧
+        let pages = split_pages(&gif, &PageRange::all()).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(shade(&pages[0]), 0);
+        let pages = split_pages(&gif, &"2".parse().unwrap()).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(shade(&pages[0]), 200);
+        assert!(matches!(
+            split_pages(&gif, &"4-5".parse().unwrap()),
+            Err(RsOcrError::Config(_))
+        ));
+
+        let single = animated_gif(&[80]);
+        let pages = split_pages(&single, &PageRange::all()).unwrap();
+        assert_eq!(pages[0].data.as_ref(), single.as_slice());
+    }
+
+    #[test]
+    fn test_single_page_formats() {
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::ImageLuma8(GrayImage::from_pixel(2, 2, Luma([0])))
+            .write_to(&mut png, ImageFormat::Png)
+            .unwrap();
+        let png = png.into_inner();
+        let pages = split_pages(&png, &"1-".parse().unwrap()).unwrap();
+        assert_eq!(
+            pages,
+            [Page {
+                number: 1,
+                data: Cow::Borrowed(png.as_slice())
+            }]
+        );
+        assert!(matches!(
+            split_pages(&png, &"2".parse().unwrap()),
+            Err(RsOcrError::Config(_))
+        ));
+        // 不是图片时交给后端处理
+        let pages = split_pages(b"not an image", &PageRange::all()).unwrap();
+        assert_eq!(pages[0].data.as_ref(), b"not an image");
+        assert!(matches!(
+            split_pages(b"II*\0\xff\xff\xff\x7f", &PageRange::all()),
+            Err(RsOcrError::ImageProcessing(_))
+        ));
+    }
+}
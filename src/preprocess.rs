@@ -0,0 +1,738 @@
+//! 上传前的图片预处理
+//!
+//! 低分辨率截图和手机照片直接上传时识别效果较差。[`Pipeline`] 先解码图片，
+//! 按顺序执行灰度化、对比度拉伸、自适应二值化、去噪、锐化和放大等步骤，
+//! 再编码为PNG上传。改变尺寸的步骤会记录缩放比例，识别结果中的坐标由
+//! [`PreprocessBackend`](crate::backend::PreprocessBackend) 换算回原图。
+//!
+//! 超大的扫描件直接上传可能超过服务器的请求体限制，传输也慢。
+//! [`Pipeline::max_side`] 和 [`Pipeline::max_bytes`] 在上传前缩小图片，
+//! 并按 [`OutputFormat`] 重新编码为PNG或指定质量的JPEG；未指定时JPEG原图仍编码为JPEG。
+//!
+//! 手机照片常用EXIF方向标记代替真正旋转像素，解码时总是先按EXIF方向转正。
+//! 扫描件放反或横放时，[`Pipeline::auto_rotate`] 让
+//! [`PreprocessBackend`](crate::backend::PreprocessBackend) 依次尝试 [`ROTATIONS`]
+//! 中的角度，保留平均置信度最高的结果。
+
+use crate::image_format::{self, ImageKind};
+use crate::models::{Result, RsOcrError};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, GrayImage, ImageDecoder, ImageFormat, ImageReader, Luma};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+/// 默认的放大倍数
+pub const DEFAULT_UPSCALE_FACTOR: f32 = 2.0;
+
+/// 放大后图片最长边的上限，避免超大图片耗尽内存
+pub const MAX_UPSCALE_SIDE: u32 = 8192;
+
+/// 默认的JPEG质量
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// 按字节上限压缩时JPEG质量的下限，低于它时改为缩小图片
+const MIN_JPEG_QUALITY: u8 = 50;
+
+/// 按字节上限缩小时图片最长边的下限
+const MIN_SIDE: u32 = 64;
+
+/// 自适应二值化时像素比邻域均值暗多少（百分比）才视为前景
+const BINARIZE_THRESHOLD: u64 = 15;
+
+/// 对比度拉伸时两端各忽略的像素比例
+const CONTRAST_CLIP: f64 = 0.01;
+
+/// 自动检测方向时尝试的顺时针旋转角度
+pub const ROTATIONS: [u32; 4] = [0, 90, 180, 270];
+
+/// 预处理步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum PreprocessStep {
+    /// 转为灰度图
+    Grayscale,
+    /// 对比度拉伸：把亮度的1%~99%分位数拉伸到 0~255
+    Contrast,
+    /// 自适应二值化（Bradley 局部均值阈值），适合光照不均的照片
+    Binarize,
+    /// 3×3 中值滤波去除椒盐噪点
+    Denoise,
+    /// USM锐化
+    Sharpen,
+    /// 按倍数放大，适合低分辨率截图
+    Upscale,
+}
+
+impl PreprocessStep {
+    /// 所有步骤，按推荐的执行顺序排列
+    pub const ALL: [PreprocessStep; 6] = [
+        PreprocessStep::Grayscale,
+        PreprocessStep::Denoise,
+        PreprocessStep::Contrast,
+        PreprocessStep::Upscale,
+        PreprocessStep::Sharpen,
+        PreprocessStep::Binarize,
+    ];
+
+    /// 配置文件和命令行中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PreprocessStep::Grayscale => "grayscale",
+            PreprocessStep::Contrast => "contrast",
+            PreprocessStep::Binarize => "binarize",
+            PreprocessStep::Denoise => "denoise",
+            PreprocessStep::Sharpen => "sharpen",
+            PreprocessStep::Upscale => "upscale",
+        }
+    }
+
+    /// 图形界面中的显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            PreprocessStep::Grayscale => "灰度",
+            PreprocessStep::Contrast => "对比度",
+            PreprocessStep::Binarize => "二值化",
+            PreprocessStep::Denoise => "去噪",
+            PreprocessStep::Sharpen => "锐化",
+            PreprocessStep::Upscale => "放大",
+        }
+    }
+}
+
+/// 上传时的图片编码格式
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// 无损的PNG，适合截图
+    #[default]
+    Png,
+    /// 有损的JPEG，适合照片和扫描件，体积更小
+    Jpeg,
+}
+
+/// 预处理后的图片
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedImage {
+    /// 重新编码的图片，不需要处理时为原图
+    pub data: Vec<u8>,
+    /// 处理后与原图宽度之比
+    pub scale_x: f64,
+    /// 处理后与原图高度之比
+    pub scale_y: f64,
+}
+
+/// 预处理流程
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::preprocess::{OutputFormat, Pipeline, PreprocessStep};
+///
+/// fn main() -> rsocr::Result<()> {
+///     let pipeline = Pipeline::new([PreprocessStep::Grayscale, PreprocessStep::Upscale])
+///         .upscale_factor(3.0);
+///     let processed = pipeline.process(&std::fs::read("screenshot.png")?)?;
+///     std::fs::write("processed.png", &processed.data)?;
+///
+///     // 缩小到最长边4000像素、4MB以内，编码为JPEG
+///     let pipeline = Pipeline::new([])
+///         .max_side(4000)
+///         .max_bytes(4 * 1024 * 1024)
+///         .output_format(OutputFormat::Jpeg);
+///     let processed = pipeline.process(&std::fs::read("scan.tiff")?)?;
+///     println!("缩放比例 {}", processed.scale_x);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pipeline {
+    steps: Vec<PreprocessStep>,
+    upscale_factor: f32,
+    binarize_window: Option<u32>,
+    max_side: Option<u32>,
+    max_bytes: Option<u64>,
+    format: Option<OutputFormat>,
+    jpeg_quality: u8,
+    auto_rotate: bool,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl Pipeline {
+    /// 创建按给定顺序执行的预处理流程
+    pub fn new(steps: impl IntoIterator<Item = PreprocessStep>) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            upscale_factor: DEFAULT_UPSCALE_FACTOR,
+            binarize_window: None,
+            max_side: None,
+            max_bytes: None,
+            format: None,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+            auto_rotate: false,
+        }
+    }
+
+    /// 设置放大倍数，小于等于1时不放大
+    pub fn upscale_factor(mut self, factor: f32) -> Self {
+        self.upscale_factor = factor;
+        self
+    }
+
+    /// 设置二值化的邻域边长（像素），默认为图片最长边的1/16
+    pub fn binarize_window(mut self, window: u32) -> Self {
+        self.binarize_window = Some(window);
+        self
+    }
+
+    /// 设置图片最长边的上限（像素），超过时等比缩小
+    pub fn max_side(mut self, max_side: u32) -> Self {
+        self.max_side = Some(max_side.max(1));
+        self
+    }
+
+    /// 设置编码后图片的字节数上限，超过时先降低JPEG质量，再缩小图片
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// 设置上传时的编码格式，不设置时只在需要重新编码时使用：JPEG原图仍为JPEG，其他格式为PNG
+    pub fn output_format(mut self, format: OutputFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// 设置JPEG质量（1~100），默认为 [`DEFAULT_JPEG_QUALITY`]
+    pub fn jpeg_quality(mut self, quality: u8) -> Self {
+        self.jpeg_quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// 设置是否自动检测方向：分别旋转 [`ROTATIONS`] 中的角度识别，保留平均置信度最高的结果
+    ///
+    /// 每张图片需要识别四次。
+    pub fn auto_rotate(mut self, enabled: bool) -> Self {
+        self.auto_rotate = enabled;
+        self
+    }
+
+    /// 预处理步骤
+    pub fn steps(&self) -> &[PreprocessStep] {
+        &self.steps
+    }
+
+    /// 是否自动检测方向
+    pub fn is_auto_rotate(&self) -> bool {
+        self.auto_rotate
+    }
+
+    /// 是否既没有步骤、尺寸限制和编码格式，也不自动检测方向，
+    /// 即只检查格式和EXIF方向，普遍支持且方向正确的图片上传原图
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+            && self.max_side.is_none()
+            && self.max_bytes.is_none()
+            && self.format.is_none()
+            && !self.auto_rotate
+    }
+
+    /// 解码图片、执行预处理并重新编码
+    ///
+    /// 格式按文件头判断，带有EXIF方向的图片先转正。没有预处理步骤、没有指定编码格式、
+    /// 格式普遍受支持、没有EXIF旋转且图片不超过尺寸限制时，直接返回原图。
+    ///
+    /// # 参数
+    /// * `image` - 图片文件的原始字节
+    ///
+    /// # 返回
+    /// * `Result<ProcessedImage>` - 不是图片、无法解码、编码或无法压缩到字节数上限以内时返回
+    ///   [`RsOcrError::ImageProcessing`]
+    pub fn process(&self, image: &[u8]) -> Result<ProcessedImage> {
+        let kind = image_format::detect(image)?;
+        if self.steps.is_empty()
+            && self.format.is_none()
+            && kind.is_widely_supported()
+            && image_format::orientation(Cursor::new(image), kind) == Orientation::NoTransforms
+            && self.fits(image, kind)?
+        {
+            return Ok(ProcessedImage {
+                data: image.to_vec(),
+                scale_x: 1.0,
+                scale_y: 1.0,
+            });
+        }
+
+        self.process_image(decode(image)?, kind)
+    }
+
+    /// 对已解码的图片执行预处理并编码，坐标比例相对于传入的图片
+    ///
+    /// # 参数
+    /// * `image` - 已解码的图片
+    /// * `source` - 原图的格式，未指定编码格式时用于选择JPEG或PNG
+    ///
+    /// # 返回
+    /// * `Result<ProcessedImage>` - 无法编码或无法压缩到字节数上限以内时返回
+    ///   [`RsOcrError::ImageProcessing`]
+    pub fn process_image(&self, image: DynamicImage, source: ImageKind) -> Result<ProcessedImage> {
+        let (width, height) = (image.width(), image.height());
+
+        let (processed, data) = self.encode_within_budget(self.apply(image), source)?;
+        Ok(ProcessedImage {
+            scale_x: processed.width() as f64 / width as f64,
+            scale_y: processed.height() as f64 / height as f64,
+            data,
+        })
+    }
+
+    /// 对已解码的图片执行预处理，最后按最长边上限缩小
+    pub fn apply(&self, image: DynamicImage) -> DynamicImage {
+        let mut image = flatten(image);
+        for step in &self.steps {
+            image = match step {
+                PreprocessStep::Grayscale => DynamicImage::ImageLuma8(image.to_luma8()),
+                PreprocessStep::Contrast => stretch_contrast(image),
+                PreprocessStep::Binarize => {
+                    DynamicImage::ImageLuma8(binarize(&image.to_luma8(), self.binarize_window))
+                }
+                PreprocessStep::Denoise => median_filter(image),
+                PreprocessStep::Sharpen => image.unsharpen(1.0, 2),
+                PreprocessStep::Upscale => upscale(image, self.upscale_factor),
+            };
+        }
+        match self.max_side {
+            Some(max_side) => downscale(image, max_side),
+            None => image,
+        }
+    }
+
+    /// 原图是否不超过尺寸和字节数上限，只读取文件头
+    fn fits(&self, image: &[u8], kind: ImageKind) -> Result<bool> {
+        if self.max_bytes.is_some_and(|max| image.len() as u64 > max) {
+            return Ok(false);
+        }
+        let Some(max_side) = self.max_side else {
+            return Ok(true);
+        };
+        let (width, height) = ImageReader::with_format(Cursor::new(image), kind.image_format())
+            .into_dimensions()
+            .map_err(|e| RsOcrError::ImageProcessing(format!("无法解码图片: {}", e)))?;
+        Ok(width.max(height) <= max_side)
+    }
+
+    /// 编码图片，超过字节数上限时先降低JPEG质量，再按比例缩小后重试
+    ///
+    /// 未指定编码格式时JPEG原图仍编码为JPEG，避免照片转成PNG后体积反而变大。
+    fn encode_within_budget(
+        &self,
+        mut image: DynamicImage,
+        source: ImageKind,
+    ) -> Result<(DynamicImage, Vec<u8>)> {
+        let format = self.format.unwrap_or(match source {
+            ImageKind::Jpeg => OutputFormat::Jpeg,
+            _ => OutputFormat::default(),
+        });
+        let mut quality = self.jpeg_quality;
+        loop {
+            let data = encode(&image, format, quality)?;
+            let Some(max_bytes) = self.max_bytes else {
+                return Ok((image, data));
+            };
+            if data.len() as u64 <= max_bytes {
+                return Ok((image, data));
+            }
+
+            if format == OutputFormat::Jpeg && quality > MIN_JPEG_QUALITY {
+                quality = quality.saturating_sub(10).max(MIN_JPEG_QUALITY);
+                continue;
+            }
+            let longest = image.width().max(image.height());
+            if longest <= MIN_SIDE {
+                return Err(RsOcrError::ImageProcessing(format!(
+                    "图片无法压缩到 {} 字节以内",
+                    max_bytes
+                )));
+            }
+            // 编码后的大小大致与像素数成正比
+            let ratio = (max_bytes as f64 / data.len() as f64).sqrt() * 0.9;
+            let target = (longest as f64 * ratio.min(0.9)) as u32;
+            image = downscale(image, target.max(MIN_SIDE));
+        }
+    }
+}
+
+/// 按文件头判断格式并解码图片，按EXIF方向转正
+///
+/// # 返回
+/// * `Result<DynamicImage>` - 不是图片或无法解码时返回 [`RsOcrError::ImageProcessing`]
+pub fn decode(image: &[u8]) -> Result<DynamicImage> {
+    let kind = image_format::detect(image)?;
+    let error = |e: image::ImageError| RsOcrError::ImageProcessing(format!("无法解码图片: {}", e));
+    let mut decoder = ImageReader::with_format(Cursor::new(image), kind.image_format())
+        .into_decoder()
+        .map_err(error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(error)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// 顺时针旋转图片，`degrees` 只支持 90、180 和 270，其他值返回原图
+pub fn rotate(image: &DynamicImage, degrees: u32) -> DynamicImage {
+    match degrees {
+        90 => image.rotate90(),
+        180 => image.rotate180(),
+        270 => image.rotate270(),
+        _ => image.clone(),
+    }
+}
+
+/// 按指定格式编码，`quality` 只对JPEG有效
+fn encode(image: &DynamicImage, format: OutputFormat, quality: u8) -> Result<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+    match format {
+        OutputFormat::Png => image.write_to(&mut data, ImageFormat::Png),
+        OutputFormat::Jpeg => JpegEncoder::new_with_quality(&mut data, quality).encode_image(image),
+    }
+    .map_err(|e| RsOcrError::ImageProcessing(format!("无法编码图片: {}", e)))?;
+    Ok(data.into_inner())
+}
+
+/// 统一为8位的灰度或RGB图，透明部分按白色背景合成
+fn flatten(image: DynamicImage) -> DynamicImage {
+    let blend = |value: u8, alpha: u8| {
+        let (value, alpha) = (value as u32, alpha as u32);
+        ((value * alpha + 255 * (255 - alpha)) / 255) as u8
+    };
+
+    match (image.color().has_color(), image.color().has_alpha()) {
+        (false, false) => DynamicImage::ImageLuma8(image.to_luma8()),
+        (true, false) => DynamicImage::ImageRgb8(image.to_rgb8()),
+        (false, true) => {
+            let source = image.to_luma_alpha8();
+            DynamicImage::ImageLuma8(GrayImage::from_fn(
+                source.width(),
+                source.height(),
+                |x, y| {
+                    let [value, alpha] = source.get_pixel(x, y).0;
+                    Luma([blend(value, alpha)])
+                },
+            ))
+        }
+        (true, true) => {
+            let source = image.to_rgba8();
+            DynamicImage::ImageRgb8(image::RgbImage::from_fn(
+                source.width(),
+                source.height(),
+                |x, y| {
+                    let [r, g, b, alpha] = source.get_pixel(x, y).0;
+                    image::Rgb([blend(r, alpha), blend(g, alpha), blend(b, alpha)])
+                },
+            ))
+        }
+    }
+}
+
+/// 把亮度的分位数区间线性拉伸到 0~255，各通道使用同一个映射
+fn stretch_contrast(mut image: DynamicImage) -> DynamicImage {
+    let luma = image.to_luma8();
+    let mut histogram = [0u64; 256];
+    for pixel in luma.pixels() {
+        histogram[pixel.0[0] as usize] += 1;
+    }
+
+    let total = luma.pixels().len() as f64;
+    let percentile = |fraction: f64| {
+        let target = (total * fraction) as u64;
+        let mut count = 0;
+        histogram
+            .iter()
+            .position(|&n| {
+                count += n;
+                count > target
+            })
+            .unwrap_or(255) as i32
+    };
+    let (low, high) = (percentile(CONTRAST_CLIP), percentile(1.0 - CONTRAST_CLIP));
+    if high <= low {
+        return image;
+    }
+
+    let lut: Vec<u8> = (0..256)
+        .map(|v| ((v - low) * 255 / (high - low)).clamp(0, 255) as u8)
+        .collect();
+    match &mut image {
+        DynamicImage::ImageLuma8(buffer) => buffer.iter_mut().for_each(|v| *v = lut[*v as usize]),
+        DynamicImage::ImageRgb8(buffer) => buffer.iter_mut().for_each(|v| *v = lut[*v as usize]),
+        _ => {}
+    }
+    image
+}
+
+/// Bradley 自适应二值化：比邻域均值暗 [`BINARIZE_THRESHOLD`]% 以上的像素为黑色
+fn binarize(image: &GrayImage, window: Option<u32>) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let window = window.unwrap_or(width.max(height) / 16).max(3);
+    let half = window / 2;
+
+    // 积分图，多一行一列便于计算
+    let stride = width as usize + 1;
+    let mut integral = vec![0u64; stride * (height as usize + 1)];
+    for y in 0..height as usize {
+        let mut row_sum = 0u64;
+        for x in 0..width as usize {
+            row_sum += image.get_pixel(x as u32, y as u32).0[0] as u64;
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row_sum;
+        }
+    }
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let x0 = x.saturating_sub(half) as usize;
+        let y0 = y.saturating_sub(half) as usize;
+        let x1 = (x + half + 1).min(width) as usize;
+        let y1 = (y + half + 1).min(height) as usize;
+        let sum = integral[y1 * stride + x1] + integral[y0 * stride + x0]
+            - integral[y0 * stride + x1]
+            - integral[y1 * stride + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+
+        let value = image.get_pixel(x, y).0[0] as u64;
+        if value * count * 100 <= sum * (100 - BINARIZE_THRESHOLD) {
+            Luma([0])
+        } else {
+            Luma([255])
+        }
+    })
+}
+
+/// 3×3 中值滤波，边缘像素按最近的像素补齐
+fn median_filter(image: DynamicImage) -> DynamicImage {
+    fn filter(data: &[u8], output: &mut [u8], width: usize, height: usize, channels: usize) {
+        let mut window = [0u8; 9];
+        for y in 0..height {
+            for x in 0..width {
+                for c in 0..channels {
+                    let mut i = 0;
+                    for dy in [-1isize, 0, 1] {
+                        for dx in [-1isize, 0, 1] {
+                            let sx = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+                            let sy = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+                            window[i] = data[(sy * width + sx) * channels + c];
+                            i += 1;
+                        }
+                    }
+                    window.sort_unstable();
+                    output[(y * width + x) * channels + c] = window[4];
+                }
+            }
+        }
+    }
+
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    match image {
+        DynamicImage::ImageLuma8(buffer) => {
+            let mut output = buffer.clone();
+            filter(&buffer, &mut output, width, height, 1);
+            DynamicImage::ImageLuma8(output)
+        }
+        other => {
+            let buffer = other.to_rgb8();
+            let mut output = buffer.clone();
+            filter(&buffer, &mut output, width, height, 3);
+            DynamicImage::ImageRgb8(output)
+        }
+    }
+}
+
+/// 按倍数放大，最长边不超过 [`MAX_UPSCALE_SIDE`]
+fn upscale(image: DynamicImage, factor: f32) -> DynamicImage {
+    let longest = image.width().max(image.height()) as f32;
+    let factor = factor.min(MAX_UPSCALE_SIDE as f32 / longest);
+    if factor <= 1.0 {
+        return image;
+    }
+
+    let width = (image.width() as f32 * factor).round() as u32;
+    let height = (image.height() as f32 * factor).round() as u32;
+    image.resize_exact(width, height, FilterType::CatmullRom)
+}
+
+/// 等比缩小到最长边不超过 `max_side`
+fn downscale(image: DynamicImage, max_side: u32) -> DynamicImage {
+    let longest = image.width().max(image.height());
+    if longest <= max_side {
+        return image;
+    }
+
+    let factor = max_side as f64 / longest as f64;
+    let width = ((image.width() as f64 * factor).round() as u32).max(1);
+    let height = ((image.height() as f64 * factor).round() as u32).max(1);
+    image.resize_exact(width, height, FilterType::CatmullRom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+        encode(image, OutputFormat::Png, DEFAULT_JPEG_QUALITY)
+    }
+
+    fn decode(data: &[u8]) -> DynamicImage {
+        image::load_from_memory_with_format(data, ImageFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn test_grayscale_and_upscale() -> Result<()> {
+        let source = RgbaImage::from_fn(40, 20, |x, _| {
+            if x < 20 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        });
+        let png = encode_png(&DynamicImage::ImageRgba8(source))?;
+
+        let processed = Pipeline::new([PreprocessStep::Grayscale, PreprocessStep::Upscale])
+            .upscale_factor(2.5)
+            .process(&png)?;
+        assert_eq!((processed.scale_x, processed.scale_y), (2.5, 2.5));
+
+        let image = decode(&processed.data);
+        assert_eq!(image.color(), image::ColorType::L8);
+        assert_eq!((image.width(), image.height()), (100, 50));
+        // 透明部分按白色背景合成
+        assert_eq!(image.to_luma8().get_pixel(99, 49).0, [255]);
+        assert!(image.to_luma8().get_pixel(0, 0).0[0] < 128);
+
+        let err = Pipeline::new([PreprocessStep::Grayscale])
+            .process(b"not an image")
+            .unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_contrast_and_denoise() {
+        // 亮度只在 100~140 之间的低对比度图片，带一个噪点
+        let mut source = GrayImage::from_fn(50, 50, |x, _| Luma([100 + (x * 40 / 49) as u8]));
+        source.put_pixel(25, 25, Luma([0]));
+
+        let image = Pipeline::new([PreprocessStep::Denoise, PreprocessStep::Contrast])
+            .apply(DynamicImage::ImageLuma8(source))
+            .to_luma8();
+        assert_eq!(image.get_pixel(0, 0).0, [0]);
+        assert_eq!(image.get_pixel(49, 0).0, [255]);
+        let (left, right) = (image.get_pixel(24, 25).0[0], image.get_pixel(26, 25).0[0]);
+        let middle = image.get_pixel(25, 25).0[0];
+        assert!(left <= middle && middle <= right);
+
+        // 颜色通道保留
+        let color = Pipeline::new([PreprocessStep::Sharpen, PreprocessStep::Contrast]).apply(
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 8, Rgb([10, 200, 30]))),
+        );
+        assert_eq!(color.color(), image::ColorType::Rgb8);
+    }
+
+    #[test]
+    fn test_size_limits() -> Result<()> {
+        // 像素无规律的图片，PNG和JPEG都压缩不了多少
+        let noise = RgbImage::from_fn(400, 200, |x, y| {
+            let v = (x * 7919 + y * 104_729) % 251;
+            Rgb([v as u8, (v * 3 % 251) as u8, (v * 5 % 251) as u8])
+        });
+        let png = encode_png(&DynamicImage::ImageRgb8(noise.clone()))?;
+
+        // 不超过上限时原样返回
+        let processed = Pipeline::new([]).max_side(400).process(&png)?;
+        assert_eq!(processed.data, png);
+        assert_eq!(processed.scale_x, 1.0);
+
+        let processed = Pipeline::new([]).max_side(100).process(&png)?;
+        let image = decode(&processed.data);
+        assert_eq!((image.width(), image.height()), (100, 50));
+        assert_eq!((processed.scale_x, processed.scale_y), (0.25, 0.25));
+
+        let processed = Pipeline::new([])
+            .max_bytes(20_000)
+            .output_format(OutputFormat::Jpeg)
+            .jpeg_quality(95)
+            .process(&png)?;
+        assert!(processed.data.len() <= 20_000);
+        assert!(processed.data.starts_with(&[0xFF, 0xD8, 0xFF]));
+        let image = image::load_from_memory(&processed.data).unwrap();
+        assert_eq!(processed.scale_x, image.width() as f64 / 400.0);
+
+        let processed = Pipeline::new([]).max_bytes(20_000).process(&png)?;
+        assert!(processed.data.len() <= 20_000);
+        assert!(processed.data.starts_with(b"\x89PNG"));
+        assert!(processed.scale_x < 1.0);
+
+        // 未指定编码格式时JPEG照片仍以JPEG上传
+        let jpeg = encode(&DynamicImage::ImageRgb8(noise), OutputFormat::Jpeg, 95)?;
+        let processed = Pipeline::new([]).max_bytes(20_000).process(&jpeg)?;
+        assert!(processed.data.len() <= 20_000);
+        assert!(processed.data.starts_with(&[0xFF, 0xD8, 0xFF]));
+
+        let err = Pipeline::new([]).max_bytes(10).process(&png).unwrap_err();
+        assert!(matches!(err, RsOcrError::ImageProcessing(ref m) if m.contains("10 字节")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exif_orientation() -> Result<()> {
+        use crate::test_support::exif_jpeg;
+
+        // 横拍的照片按EXIF方向转正后上传
+        let photo = exif_jpeg(6, 4, Orientation::Rotate90);
+        let processed = Pipeline::new([]).process(&photo)?;
+        assert!(processed.data.starts_with(&[0xFF, 0xD8, 0xFF]));
+        let image = image::load_from_memory(&processed.data).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 6));
+        assert_eq!((processed.scale_x, processed.scale_y), (1.0, 1.0));
+        let image = super::decode(&photo)?;
+        assert_eq!((image.width(), image.height()), (4, 6));
+
+        let upright = exif_jpeg(6, 4, Orientation::NoTransforms);
+        assert_eq!(Pipeline::new([]).process(&upright)?.data, upright);
+
+        let rotated = rotate(&image, 270);
+        assert_eq!((rotated.width(), rotated.height()), (6, 4));
+        assert_eq!(rotate(&image, 45).width(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_binarize_uneven_lighting() {
+        // 从左到右变亮的背景上有两条比周围暗的“笔画”
+        let source = GrayImage::from_fn(64, 32, |x, y| {
+            let background = 60 + x as u8 * 3;
+            if (x == 10 || x == 50) && (8..24).contains(&y) {
+                Luma([background / 2])
+            } else {
+                Luma([background])
+            }
+        });
+
+        let image = Pipeline::new([PreprocessStep::Binarize])
+            .binarize_window(15)
+            .apply(DynamicImage::ImageLuma8(source))
+            .to_luma8();
+        assert!(image.pixels().all(|p| p.0[0] == 0 || p.0[0] == 255));
+        assert_eq!(image.get_pixel(10, 16).0, [0]);
+        assert_eq!(image.get_pixel(50, 16).0, [0]);
+        assert_eq!(image.get_pixel(2, 16).0, [255]);
+        assert_eq!(image.get_pixel(60, 2).0, [255]);
+    }
+}
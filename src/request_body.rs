@@ -0,0 +1,209 @@
+//! 流式生成的OCR请求体
+//!
+//! [`image_to_base64`](crate::image_to_base64) 先把整个文件读入内存，再编码为base64字符串，
+//! 序列化请求时又会复制一次，识别几十MB的扫描件时内存占用约为文件大小的四倍。
+//! [`OcrRequestBody`] 边读取文件边编码，直接写入HTTP请求体，
+//! 内存占用与文件大小无关（见 `benches/peak_memory.rs`）。
+
+use crate::models::{OcrOptions, Result, RsOcrError};
+use base64::{Engine as _, engine::general_purpose};
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+/// 每次从源读取的字节数，必须是3的倍数，这样各段编码结果可以直接拼接
+const CHUNK_SIZE: usize = 3 * 16 * 1024;
+
+/// 把源数据编码为标准base64的读取器
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::request_body::Base64Reader;
+/// use std::io::Read;
+///
+/// let mut encoded = String::new();
+/// Base64Reader::new(&b"hello"[..]).read_to_string(&mut encoded).unwrap();
+/// assert_eq!(encoded, "aGVsbG8=");
+/// ```
+pub struct Base64Reader<R> {
+    source: R,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> Base64Reader<R> {
+    /// 包装源数据
+    pub fn new(source: R) -> Self {
+        Self {
+            source,
+            input: vec![0; CHUNK_SIZE],
+            output: Vec::with_capacity(CHUNK_SIZE / 3 * 4),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// 读取下一段源数据并编码，除最后一段外长度都是3的倍数
+    fn fill(&mut self) -> io::Result<()> {
+        let mut filled = 0;
+        while filled < self.input.len() {
+            match self.source.read(&mut self.input[filled..]) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.output.clear();
+        self.output
+            .resize(base64::encoded_len(filled, true).unwrap_or_default(), 0);
+        let written = general_purpose::STANDARD
+            .encode_slice(&self.input[..filled], &mut self.output)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        self.output.truncate(written);
+        self.pos = 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Base64Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+
+        let n = buf.len().min(self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// 流式生成的OCR请求体，内容与序列化 [`OcrRequest`](crate::OcrRequest) 相同
+///
+/// # 示例
+///
+/// ```no_run
+/// use rsocr::OcrOptions;
+/// use rsocr::request_body::OcrRequestBody;
+///
+/// fn main() -> rsocr::Result<()> {
+///     let mut body = OcrRequestBody::from_file("scan.png", &OcrOptions::default())?;
+///     println!("{} 字节", body.content_length());
+///     std::io::copy(&mut body, &mut std::io::sink())?;
+///     Ok(())
+/// }
+/// ```
+pub struct OcrRequestBody {
+    reader: Box<dyn Read + Send>,
+    content_length: u64,
+}
+
+impl OcrRequestBody {
+    /// 打开图片文件，生成请求体
+    ///
+    /// # 参数
+    /// * `image_path` - 图片文件路径
+    /// * `options` - OCR请求选项，按原样写入请求体
+    ///
+    /// # 返回
+    /// * `Result<OcrRequestBody>` - 文件无法打开时返回 [`RsOcrError::Io`]
+    pub fn from_file(image_path: impl AsRef<Path>, options: &OcrOptions) -> Result<Self> {
+        let file = File::open(image_path).map_err(RsOcrError::Io)?;
+        let size = file.metadata().map_err(RsOcrError::Io)?.len();
+        Self::from_reader(file, size, options)
+    }
+
+    /// 从任意数据源生成请求体
+    ///
+    /// # 参数
+    /// * `source` - 图片数据
+    /// * `size` - 图片数据的字节数，用于计算 `Content-Length`
+    /// * `options` - OCR请求选项，按原样写入请求体
+    pub fn from_reader(
+        source: impl Read + Send + 'static,
+        size: u64,
+        options: &OcrOptions,
+    ) -> Result<Self> {
+        // base64字符不需要JSON转义，可以直接写在引号之间
+        let prefix = b"{\"base64\":\"".to_vec();
+        let suffix = format!("\",\"options\":{}}}", serde_json::to_string(options)?).into_bytes();
+        let content_length = prefix.len() as u64 + size.div_ceil(3) * 4 + suffix.len() as u64;
+
+        let reader = Cursor::new(prefix)
+            .chain(Base64Reader::new(source.take(size)))
+            .chain(Cursor::new(suffix));
+        Ok(Self {
+            reader: Box::new(reader),
+            content_length,
+        })
+    }
+
+    /// 请求体的总字节数
+    pub fn content_length(&self) -> u64 {
+        self.content_length
+    }
+}
+
+impl Read for OcrRequestBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl From<OcrRequestBody> for reqwest::blocking::Body {
+    fn from(body: OcrRequestBody) -> Self {
+        let length = body.content_length;
+        reqwest::blocking::Body::sized(body, length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DataFormat, OcrRequest};
+    use std::io::Write;
+
+    #[test]
+    fn test_matches_serialized_request() -> Result<()> {
+        let options = OcrOptions {
+            data_format: DataFormat::Dict,
+            language: Some("models/config_chinese.txt".to_string()),
+            ..Default::default()
+        };
+
+        // 覆盖不是3的倍数以及跨越多个编码段的长度
+        for size in [0, 1, 2, 3, CHUNK_SIZE - 1, CHUNK_SIZE + 2, CHUNK_SIZE * 3] {
+            let data: Vec<u8> = (0..size).map(|i| (i * 7 % 251) as u8).collect();
+            let mut file = tempfile::NamedTempFile::new()?;
+            file.write_all(&data)?;
+
+            let mut body = OcrRequestBody::from_file(file.path(), &options)?;
+            let mut streamed = Vec::new();
+            body.read_to_end(&mut streamed)?;
+
+            let expected = serde_json::to_vec(&OcrRequest {
+                base64: general_purpose::STANDARD.encode(&data),
+                options: options.clone(),
+            })?;
+            assert_eq!(streamed, expected, "size {}", size);
+            assert_eq!(body.content_length(), expected.len() as u64);
+        }
+
+        assert!(matches!(
+            OcrRequestBody::from_file("/nonexistent/image.png", &options),
+            Err(RsOcrError::Io(_))
+        ));
+        Ok(())
+    }
+}
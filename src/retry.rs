@@ -0,0 +1,378 @@
+//! 重试策略与熔断器
+//!
+//! [`RetryPolicy`] 决定哪些失败可以重试以及每次重试前等待多久（指数退避加随机抖动）；
+//! [`CircuitBreaker`] 在连续失败达到阈值后暂停请求，冷却结束后放行一个探测请求，
+//! 探测成功则恢复正常。两者都通过 [`OcrClientBuilder`](crate::OcrClientBuilder)
+//! 或配置文件的 `[retry]`、`[circuit_breaker]` 表启用。
+
+use crate::models::{Result, RsOcrError};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 默认可重试的HTTP状态码
+pub const DEFAULT_RETRY_STATUS: &[u16] = &[429, 500, 502, 503, 504];
+
+/// 重试策略
+///
+/// # 示例
+///
+/// ```
+/// use rsocr::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     initial_backoff: Duration::from_millis(200),
+///     jitter: 0.0,
+///     ..Default::default()
+/// };
+/// assert_eq!(policy.backoff(1), Duration::from_millis(200));
+/// assert_eq!(policy.backoff(2), Duration::from_millis(400));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最多尝试的次数（包括第一次请求），1 表示不重试
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间
+    pub initial_backoff: Duration,
+    /// 等待时间的上限
+    pub max_backoff: Duration,
+    /// 每次重试后等待时间的倍数
+    pub multiplier: f64,
+    /// 随机抖动比例（0 ~ 1），实际等待时间在 `[退避 × (1 - jitter), 退避]` 之间
+    pub jitter: f64,
+    /// 可重试的HTTP状态码
+    pub retry_status: Vec<u16>,
+    /// 连接失败时是否重试
+    pub retry_connect: bool,
+    /// 请求超时时是否重试
+    pub retry_timeout: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            retry_status: DEFAULT_RETRY_STATUS.to_vec(),
+            retry_connect: true,
+            retry_timeout: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试的策略
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// 第 `retry` 次重试（从1开始）前的等待时间
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(64) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let base = base.min(self.max_backoff.as_secs_f64());
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        Duration::from_secs_f64(base * (1.0 - jitter * random_unit()))
+    }
+
+    /// HTTP状态码是否可以重试
+    pub fn is_retryable_status(&self, status: u16) -> bool {
+        self.retry_status.contains(&status)
+    }
+
+    /// 请求错误是否可以重试
+    pub fn is_retryable_error(&self, err: &reqwest::Error) -> bool {
+        (self.retry_connect && err.is_connect()) || (self.retry_timeout && err.is_timeout())
+    }
+}
+
+/// `[0, 1)` 之间的随机数，用于退避抖动
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(Instant::now().elapsed().as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+/// 熔断器
+///
+/// 克隆出的实例共享同一个状态，因此同一个 [`OcrClient`](crate::OcrClient) 的所有克隆
+/// 共同计数。
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cool_down: Duration,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl CircuitBreaker {
+    /// 创建熔断器
+    ///
+    /// # 参数
+    /// * `failure_threshold` - 连续失败多少次后熔断（最小为1）
+    /// * `cool_down` - 熔断后等待多久再放行探测请求
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cool_down,
+            state: Arc::new(Mutex::new(BreakerState::Closed { failures: 0 })),
+        }
+    }
+
+    /// 熔断器当前是否处于熔断状态（拒绝请求）
+    pub fn is_open(&self) -> bool {
+        matches!(*self.lock(), BreakerState::Open { until } if Instant::now() < until)
+    }
+
+    /// 请求前检查是否允许发送
+    ///
+    /// # 返回
+    /// * `Result<()>` - 熔断中时返回 [`RsOcrError::CircuitOpen`]
+    pub fn check(&self) -> Result<()> {
+        let mut state = self.lock();
+        match *state {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    Err(RsOcrError::CircuitOpen(until - now))
+                } else {
+                    // 冷却结束，只放行一个探测请求
+                    *state = BreakerState::HalfOpen;
+                    Ok(())
+                }
+            }
+            BreakerState::HalfOpen => Err(RsOcrError::CircuitOpen(Duration::ZERO)),
+        }
+    }
+
+    /// 记录一次成功，恢复正常状态
+    pub fn record_success(&self) {
+        *self.lock() = BreakerState::Closed { failures: 0 };
+    }
+
+    /// 记录一次失败，达到阈值或探测失败时熔断
+    pub fn record_failure(&self) {
+        let mut state = self.lock();
+        let failures = match *state {
+            BreakerState::Closed { failures } => failures + 1,
+            _ => self.failure_threshold,
+        };
+
+        *state = if failures >= self.failure_threshold {
+            log::warn!(
+                "OCR服务连续失败 {} 次，暂停请求 {:?}",
+                failures,
+                self.cool_down
+            );
+            BreakerState::Open {
+                until: Instant::now() + self.cool_down,
+            }
+        } else {
+            BreakerState::Closed { failures }
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 单次请求的结果分类
+pub(crate) enum Outcome<T> {
+    /// 成功，或不需要重试的失败
+    Done(Result<T>),
+    /// 可以重试的失败
+    Retry(Result<T>),
+}
+
+impl<T> Outcome<T> {
+    /// 按重试策略对HTTP响应分类
+    pub(crate) fn classify(
+        policy: &RetryPolicy,
+        response: reqwest::Result<T>,
+        status: impl FnOnce(&T) -> u16,
+    ) -> Self {
+        match response {
+            Ok(response) if policy.is_retryable_status(status(&response)) => {
+                Outcome::Retry(Ok(response))
+            }
+            Ok(response) => Outcome::Done(Ok(response)),
+            Err(e) if policy.is_retryable_error(&e) => Outcome::Retry(Err(RsOcrError::Http(e))),
+            Err(e) => Outcome::Done(Err(RsOcrError::Http(e))),
+        }
+    }
+
+    /// 按重试策略对Unix域套接字上的响应分类
+    pub(crate) fn classify_io(
+        policy: &RetryPolicy,
+        response: std::io::Result<T>,
+        status: impl FnOnce(&T) -> u16,
+    ) -> Self {
+        use std::io::ErrorKind;
+        match response {
+            Ok(response) if policy.is_retryable_status(status(&response)) => {
+                Outcome::Retry(Ok(response))
+            }
+            Ok(response) => Outcome::Done(Ok(response)),
+            Err(e) => {
+                let retryable = match e.kind() {
+                    ErrorKind::NotFound | ErrorKind::ConnectionRefused => policy.retry_connect,
+                    ErrorKind::TimedOut | ErrorKind::WouldBlock => policy.retry_timeout,
+                    _ => false,
+                };
+                if retryable {
+                    Outcome::Retry(Err(RsOcrError::Io(e)))
+                } else {
+                    Outcome::Done(Err(RsOcrError::Io(e)))
+                }
+            }
+        }
+    }
+}
+
+/// 按重试策略和熔断器执行同步请求
+///
+/// 可重试的失败在用完次数后原样返回（响应交给调用方检查状态码）。
+/// 熔断器只在第一次尝试前检查，每个请求无论重试几次都只记录一次成功或失败。
+pub(crate) fn run<T>(
+    policy: &RetryPolicy,
+    breaker: Option<&CircuitBreaker>,
+    mut attempt: impl FnMut() -> Outcome<T>,
+) -> Result<T> {
+    if let Some(breaker) = breaker {
+        breaker.check()?;
+    }
+    let mut retry = 0;
+    loop {
+        match attempt() {
+            Outcome::Done(result) => {
+                record(breaker, result.is_ok());
+                return result;
+            }
+            Outcome::Retry(result) => {
+                retry += 1;
+                // 其他请求触发熔断时也不再重试
+                if retry >= policy.max_attempts || breaker.is_some_and(|b| b.is_open()) {
+                    record(breaker, false);
+                    return result;
+                }
+                let delay = policy.backoff(retry);
+                log::warn!("OCR请求失败，{:?}后第{}次重试", delay, retry);
+                std::thread::sleep(delay);
+            }
+        }
+    }
+}
+
+/// [`run`] 的异步版本
+#[cfg(feature = "async")]
+pub(crate) async fn run_async<T, F, Fut>(
+    policy: &RetryPolicy,
+    breaker: Option<&CircuitBreaker>,
+    mut attempt: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Outcome<T>>,
+{
+    if let Some(breaker) = breaker {
+        breaker.check()?;
+    }
+    let mut retry = 0;
+    loop {
+        match attempt().await {
+            Outcome::Done(result) => {
+                record(breaker, result.is_ok());
+                return result;
+            }
+            Outcome::Retry(result) => {
+                retry += 1;
+                // 其他请求触发熔断时也不再重试
+                if retry >= policy.max_attempts || breaker.is_some_and(|b| b.is_open()) {
+                    record(breaker, false);
+                    return result;
+                }
+                let delay = policy.backoff(retry);
+                log::warn!("OCR请求失败，{:?}后第{}次重试", delay, retry);
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+fn record(breaker: Option<&CircuitBreaker>, success: bool) {
+    if let Some(breaker) = breaker {
+        if success {
+            breaker.record_success();
+        } else {
+            breaker.record_failure();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_millis(500));
+
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..policy
+        };
+        for _ in 0..20 {
+            let delay = policy.backoff(2);
+            assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(100));
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        breaker.record_failure();
+        assert!(matches!(breaker.check(), Err(RsOcrError::CircuitOpen(_))));
+
+        // 冷却结束后只放行一个探测请求
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_err());
+
+        // 探测失败重新熔断，探测成功恢复
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert!(breaker.check().is_ok());
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,338 @@
+//! 测试辅助工具
+//!
+//! 提供一个基于 `std::net` 的最小HTTP模拟服务器，用于在本地测试OCR客户端。
+//! [`MockServer::start_tls`] 使用 `tests/fixtures/tls` 中的证书提供HTTPS，
+//! 这些证书由 `openssl` 生成，有效期100年：`ca.pem` 签发了 `server.pem`
+//! （`localhost`、`127.0.0.1`）和 `client.pem`（客户端认证）。
+//! [`exif_jpeg`] 生成带EXIF方向的测试照片。
+
+// 各模块的测试只使用其中一部分功能
+#![allow(dead_code)]
+
+use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// 模拟服务器收到的请求
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    /// 按名称（不区分大小写）查找请求头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 将请求体解析为JSON
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("请求体不是有效的JSON")
+    }
+}
+
+/// 模拟服务器返回的响应
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// 状态码为200的JSON响应
+    pub fn json(value: serde_json::Value) -> Self {
+        Self::status_json(200, value)
+    }
+
+    /// 指定状态码的JSON响应
+    pub fn status_json(status: u16, value: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    /// Umi-OCR识别成功的响应，只有一个位于 `(0, 0)`、边长为1的文本块
+    pub fn umi_text(text: &str, score: f64) -> Self {
+        Self::umi_block(text, score, [[0, 0], [1, 0], [1, 1], [0, 1]])
+    }
+
+    /// Umi-OCR识别成功的响应，只有一个指定文本框的文本块
+    pub fn umi_block(text: &str, score: f64, bbox: [[i32; 2]; 4]) -> Self {
+        Self::json(serde_json::json!({
+            "code": 100,
+            "data": [{"text": text, "score": score, "box": bbox, "end": ""}]
+        }))
+    }
+
+    /// 指定状态码的纯文本响应
+    pub fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain".to_string(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+}
+
+type Handler = dyn Fn(&MockRequest) -> MockResponse + Send + Sync;
+
+/// TLS测试证书的路径
+pub fn tls_fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+/// 本地HTTP模拟服务器，随测试结束自动停止
+pub struct MockServer {
+    scheme: &'static str,
+    addr: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    connections: Arc<AtomicUsize>,
+    /// Unix域套接字所在的临时目录
+    _socket_dir: Option<tempfile::TempDir>,
+}
+
+impl MockServer {
+    /// 在随机端口上启动服务器，每个请求都交给 `handler` 处理
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        Self::start_with(None, handler)
+    }
+
+    /// 启动HTTPS服务器
+    ///
+    /// # 参数
+    /// * `require_client_cert` - 是否要求客户端提供由测试CA签发的证书
+    pub fn start_tls<F>(require_client_cert: bool, handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor
+            .set_certificate_chain_file(tls_fixture("server.pem"))
+            .unwrap();
+        acceptor
+            .set_private_key_file(tls_fixture("server.key"), SslFiletype::PEM)
+            .unwrap();
+        if require_client_cert {
+            acceptor.set_ca_file(tls_fixture("ca.pem")).unwrap();
+            acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        }
+        Self::start_with(Some(Arc::new(acceptor.build())), handler)
+    }
+
+    fn start_with<F>(tls: Option<Arc<SslAcceptor>>, handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let scheme = if tls.is_some() { "https" } else { "http" };
+        let listener = TcpListener::bind("127.0.0.1:0").expect("无法绑定端口");
+        let addr = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let recorded = Arc::clone(&requests);
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let handler = Arc::clone(&handler);
+                let recorded = Arc::clone(&recorded);
+                let tls = tls.clone();
+                thread::spawn(move || match tls {
+                    Some(acceptor) => {
+                        if let Ok(stream) = acceptor.accept(stream) {
+                            let _ = serve_connection(stream, handler.as_ref(), &recorded);
+                        }
+                    }
+                    None => {
+                        let _ = serve_connection(stream, handler.as_ref(), &recorded);
+                    }
+                });
+            }
+        });
+
+        Self {
+            scheme,
+            addr,
+            requests,
+            connections,
+            _socket_dir: None,
+        }
+    }
+
+    /// 在临时目录中的Unix域套接字上启动服务器，[`url`](Self::url) 形如 `unix:///tmp/.../ocr.sock`
+    #[cfg(unix)]
+    pub fn start_unix<F>(handler: F) -> Self
+    where
+        F: Fn(&MockRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let dir = tempfile::tempdir().expect("无法创建临时目录");
+        let socket = dir.path().join("ocr.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&socket).expect("无法绑定套接字");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let recorded = Arc::clone(&requests);
+        let accepted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let handler = Arc::clone(&handler);
+                let recorded = Arc::clone(&recorded);
+                thread::spawn(move || {
+                    let _ = serve_connection(stream, handler.as_ref(), &recorded);
+                });
+            }
+        });
+
+        Self {
+            scheme: "unix",
+            addr: socket.display().to_string(),
+            requests,
+            connections,
+            _socket_dir: Some(dir),
+        }
+    }
+
+    /// 服务器根地址，如 `http://127.0.0.1:12345`
+    pub fn url(&self) -> String {
+        format!("{}://{}", self.scheme, self.addr)
+    }
+
+    /// 服务器地址（`host:port`，Unix域套接字为套接字路径）
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// 已接受的TCP连接数
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// 已收到的全部请求
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve_connection(
+    stream: impl Read + Write,
+    handler: &Handler,
+    recorded: &Mutex<Vec<MockRequest>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    // 支持同一连接上的多个请求（keep-alive）
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        let request = MockRequest {
+            method,
+            path,
+            body: Vec::new(),
+            headers,
+        };
+        let body = read_body(&mut reader, &request)?;
+        let request = MockRequest { body, ..request };
+
+        let response = handler(&request);
+        recorded.lock().unwrap().push(request);
+
+        let stream = reader.get_mut();
+        write!(
+            stream,
+            "HTTP/1.1 {} MOCK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n",
+            response.status,
+            response.content_type,
+            response.body.len()
+        )?;
+        stream.write_all(&response.body)?;
+        stream.flush()?;
+    }
+}
+
+fn read_body(reader: &mut impl BufRead, request: &MockRequest) -> std::io::Result<Vec<u8>> {
+    if let Some(length) = request.header("content-length") {
+        let length: usize = length.parse().unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        return Ok(body);
+    }
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        let mut body = Vec::new();
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line)?;
+            let size = usize::from_str_radix(size_line.trim(), 16).unwrap_or(0);
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                return Ok(body);
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    }
+
+    Ok(Vec::new())
+}
+
+/// 生成带EXIF方向标记的灰色JPEG，`width`、`height` 为未旋转时的像素尺寸
+pub fn exif_jpeg(width: u32, height: u32, orientation: image::metadata::Orientation) -> Vec<u8> {
+    use image::ImageEncoder;
+
+    // 只有一个 Orientation（0x0112）字段的小端TIFF头
+    let mut exif = b"II*\0\x08\0\0\0\x01\0\x12\x01\x03\0\x01\0\0\0".to_vec();
+    exif.extend_from_slice(&[orientation.to_exif(), 0, 0, 0, 0, 0, 0, 0]);
+
+    let mut data = Vec::new();
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut data);
+    encoder.set_exif_metadata(exif).unwrap();
+    let pixels = vec![128; (width * height) as usize];
+    encoder
+        .write_image(&pixels, width, height, image::ExtendedColorType::L8)
+        .unwrap();
+    data
+}
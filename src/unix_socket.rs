@@ -0,0 +1,249 @@
+//! 通过Unix域套接字发送HTTP请求
+//!
+//! OCR服务与客户端在同一台机器上时，可以把API地址写作 `unix:///path/to/sock`，
+//! 不必在 `127.0.0.1` 上开放TCP端口。默认请求 `/api/ocr`，
+//! 其他路径写在套接字路径之后，如 `unix:///run/umi-ocr.sock:/api/ocr`。
+//!
+//! 这里只实现了识别和查询参数需要的HTTP/1.1子集：每个请求一个连接，
+//! 响应体按 `Content-Length`、分块编码或读到连接关闭为止读取。
+
+use crate::models::{Result, RsOcrError};
+use reqwest::header::HeaderMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Unix域套接字地址的前缀
+pub(crate) const UNIX_SCHEME: &str = "unix://";
+
+/// 未指定路径时请求的HTTP路径
+const DEFAULT_PATH: &str = "/api/ocr";
+
+/// 是否是Unix域套接字地址
+pub(crate) fn is_unix_url(url: &str) -> bool {
+    url.starts_with(UNIX_SCHEME)
+}
+
+/// HTTP响应
+#[derive(Debug)]
+pub(crate) struct UnixResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl UnixResponse {
+    /// HTTP状态码
+    pub fn status_code(&self) -> Result<reqwest::StatusCode> {
+        reqwest::StatusCode::from_u16(self.status)
+            .map_err(|_| RsOcrError::OcrApi(format!("无效的HTTP状态码: {}", self.status)))
+    }
+}
+
+/// Unix域套接字上的HTTP传输
+#[derive(Debug, Clone)]
+pub(crate) struct UnixTransport {
+    socket: PathBuf,
+    path: String,
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+}
+
+impl UnixTransport {
+    /// 解析 `unix://` 地址
+    ///
+    /// # 参数
+    /// * `url` - 如 `unix:///run/umi-ocr.sock` 或 `unix:///run/umi-ocr.sock:/api/ocr`
+    /// * `headers` - 每个请求都会发送的请求头
+    /// * `timeout` - 读写超时
+    pub fn new(url: &str, headers: HeaderMap, timeout: Option<Duration>) -> Result<Self> {
+        let rest = url
+            .strip_prefix(UNIX_SCHEME)
+            .ok_or_else(|| RsOcrError::Config(format!("不是Unix域套接字地址: {}", url)))?;
+        let (socket, path) = match rest.split_once(":/") {
+            Some((socket, path)) => (socket, format!("/{}", path)),
+            None => (rest, DEFAULT_PATH.to_string()),
+        };
+        if socket.is_empty() {
+            return Err(RsOcrError::Config(format!("缺少套接字路径: {}", url)));
+        }
+
+        Ok(Self {
+            socket: PathBuf::from(socket),
+            path: path.trim_end_matches('/').to_string(),
+            headers,
+            timeout,
+        })
+    }
+
+    /// API路径，如 `/api/ocr`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 发送请求
+    ///
+    /// # 参数
+    /// * `method` - `GET` 或 `POST`
+    /// * `path` - HTTP路径
+    /// * `body` - JSON请求体
+    pub fn send(&self, method: &str, path: &str, body: Option<&[u8]>) -> io::Result<UnixResponse> {
+        match body {
+            Some(body) => self.send_with(method, path, Some((&mut &body[..], body.len() as u64))),
+            None => self.send_with(method, path, None),
+        }
+    }
+
+    /// 发送请求，请求体边读取边发送
+    ///
+    /// # 参数
+    /// * `method` - HTTP方法
+    /// * `path` - HTTP路径
+    /// * `body` - JSON请求体
+    /// * `length` - 请求体的字节数
+    pub fn send_reader(
+        &self,
+        method: &str,
+        path: &str,
+        mut body: impl Read,
+        length: u64,
+    ) -> io::Result<UnixResponse> {
+        self.send_with(method, path, Some((&mut body, length)))
+    }
+
+    fn send_with(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<(&mut dyn Read, u64)>,
+    ) -> io::Result<UnixResponse> {
+        let mut stream = self.connect()?;
+
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n",
+            method, path
+        );
+        for (name, value) in &self.headers {
+            head.push_str(&format!(
+                "{}: {}\r\n",
+                name,
+                String::from_utf8_lossy(value.as_bytes())
+            ));
+        }
+        if let Some((_, length)) = &body {
+            head.push_str(&format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                length
+            ));
+        }
+        head.push_str("\r\n");
+
+        stream.write_all(head.as_bytes())?;
+        if let Some((body, _)) = body {
+            io::copy(body, &mut stream)?;
+        }
+        stream.flush()?;
+
+        read_response(BufReader::new(stream))
+    }
+
+    #[cfg(unix)]
+    fn connect(&self) -> io::Result<std::os::unix::net::UnixStream> {
+        let stream = std::os::unix::net::UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        Ok(stream)
+    }
+
+    #[cfg(not(unix))]
+    fn connect(&self) -> io::Result<std::net::TcpStream> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "当前平台不支持Unix域套接字",
+        ))
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// 读取HTTP响应
+fn read_response(mut reader: impl BufRead) -> io::Result<UnixResponse> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid("无效的HTTP响应"))?;
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<usize>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            let mut size_line = String::new();
+            reader.read_line(&mut size_line)?;
+            let size = usize::from_str_radix(size_line.trim(), 16)
+                .map_err(|_| invalid("无效的分块长度"))?;
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = content_length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else {
+        reader.read_to_end(&mut body)?;
+    }
+
+    Ok(UnixResponse { status, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() -> Result<()> {
+        let transport = UnixTransport::new("unix:///run/umi.sock", HeaderMap::new(), None)?;
+        assert_eq!(transport.socket, PathBuf::from("/run/umi.sock"));
+        assert_eq!(transport.path(), "/api/ocr");
+
+        let transport =
+            UnixTransport::new("unix:///tmp/ocr.sock:/v2/ocr/", HeaderMap::new(), None)?;
+        assert_eq!(transport.socket, PathBuf::from("/tmp/ocr.sock"));
+        assert_eq!(transport.path(), "/v2/ocr");
+
+        assert!(UnixTransport::new("unix://", HeaderMap::new(), None).is_err());
+
+        let response = read_response(
+            &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n"[..],
+        )?;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"abcde");
+        Ok(())
+    }
+}